@@ -0,0 +1,152 @@
+use crate::target::Structure;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Payload sizes the harness is compiled for. Each one is a separate
+/// monomorphisation so the payload is copied inline, never allocated.
+pub const PAYLOADS: [usize; 7] = [8, 16, 32, 64, 256, 1024, 4096];
+
+pub const USAGE: &str = "\
+Usage: bench [OPTIONS]
+
+Options:
+  --structures LIST  structures to run (default: all)
+                     blocking_queue, blocking_stack, lockfree_queue,
+                     ring_queue, spsc, lockfree_stack,
+                     lockfree_stack_elim, concurrent_pool, async_queue,
+                     mpsc, mutex_vecdeque
+  --mix LIST         `pc` for dedicated producers and consumers, or a push
+                     percentage such as `50` for threads that both push and
+                     pop (default: pc,50)
+  --pairs LIST       producer x consumer counts for `pc`, e.g. 1x1,4x4
+                     (default: 1x1,2x2,4x4)
+  --threads LIST     thread counts for push-percentage mixes (default: 2,4,8)
+  --payload LIST     payload sizes in bytes, one of 8, 16, 32, 64, 256,
+                     1024, 4096 (default: 8,64)
+  --ops N            operations per run (default: 200000)
+  --runs N           runs per configuration; the median is reported
+                     (default: 3)
+  --csv PATH         write results as CSV
+  --json PATH        write results as JSON
+  -h, --help         print this message
+
+Without --csv or --json, CSV is written to stdout.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mix {
+    /// Producers only push and consumers only pop.
+    ProducerConsumer,
+    /// Every thread pushes with the given percentage and pops otherwise.
+    Mixed(u8),
+}
+
+impl fmt::Display for Mix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mix::ProducerConsumer => f.write_str("pc"),
+            Mix::Mixed(push_percent) => write!(f, "push{push_percent}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub structures: Vec<Structure>,
+    pub mixes: Vec<Mix>,
+    pub pairs: Vec<(usize, usize)>,
+    pub threads: Vec<usize>,
+    pub payloads: Vec<usize>,
+    pub ops: usize,
+    pub runs: usize,
+    pub csv: Option<PathBuf>,
+    pub json: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            structures: Structure::ALL.to_vec(),
+            mixes: vec![Mix::ProducerConsumer, Mix::Mixed(50)],
+            pairs: vec![(1, 1), (2, 2), (4, 4)],
+            threads: vec![2, 4, 8],
+            payloads: vec![8, 64],
+            ops: 200_000,
+            runs: 3,
+            csv: None,
+            json: None,
+        }
+    }
+}
+
+impl Config {
+    /// Parses the command line. `Ok(None)` means `--help` was asked for.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut config = Config::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            if flag == "-h" || flag == "--help" {
+                return Ok(None);
+            }
+            let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+            match flag.as_str() {
+                "--structures" => config.structures = parse_list(&value, parse_structure)?,
+                "--mix" => config.mixes = parse_list(&value, parse_mix)?,
+                "--pairs" => config.pairs = parse_list(&value, parse_pair)?,
+                "--threads" => config.threads = parse_list(&value, parse_count)?,
+                "--payload" => config.payloads = parse_list(&value, parse_payload)?,
+                "--ops" => config.ops = parse_count(&value)?,
+                "--runs" => config.runs = parse_count(&value)?,
+                "--csv" => config.csv = Some(value.into()),
+                "--json" => config.json = Some(value.into()),
+                _ => return Err(format!("unknown option {flag}")),
+            }
+        }
+
+        Ok(Some(config))
+    }
+}
+
+fn parse_list<T>(value: &str, parse: fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    value.split(',').map(|item| parse(item.trim())).collect()
+}
+
+fn parse_structure(value: &str) -> Result<Structure, String> {
+    Structure::ALL
+        .into_iter()
+        .find(|structure| structure.name() == value)
+        .ok_or_else(|| format!("unknown structure {value}"))
+}
+
+fn parse_mix(value: &str) -> Result<Mix, String> {
+    if value == "pc" {
+        return Ok(Mix::ProducerConsumer);
+    }
+    match value.parse() {
+        Ok(push_percent) if push_percent <= 100 => Ok(Mix::Mixed(push_percent)),
+        _ => Err(format!("mix must be `pc` or a percentage, got {value}")),
+    }
+}
+
+fn parse_pair(value: &str) -> Result<(usize, usize), String> {
+    let (producers, consumers) = value
+        .split_once('x')
+        .ok_or_else(|| format!("pair must look like 2x2, got {value}"))?;
+
+    Ok((parse_count(producers)?, parse_count(consumers)?))
+}
+
+fn parse_count(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("expected a positive number, got {value}")),
+    }
+}
+
+fn parse_payload(value: &str) -> Result<usize, String> {
+    let size = parse_count(value)?;
+    if !PAYLOADS.contains(&size) {
+        return Err(format!("payload must be one of {PAYLOADS:?}, got {size}"));
+    }
+
+    Ok(size)
+}
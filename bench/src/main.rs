@@ -0,0 +1,120 @@
+mod config;
+mod report;
+mod run;
+mod target;
+
+use config::{Config, Mix, USAGE};
+use report::Sample;
+use run::Workload;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut samples = Vec::new();
+    for &payload in &config.payloads {
+        for &mix in &config.mixes {
+            for &structure in &config.structures {
+                for workload in workloads(&config, mix) {
+                    if let Some(sample) = sample(&config, structure, workload, payload) {
+                        eprintln!(
+                            "{:>16} {:>7} {:>10} {:>5}B {:>14.0} ops/s",
+                            structure.name(),
+                            mix.to_string(),
+                            workload.to_string(),
+                            payload,
+                            sample.ops_per_sec,
+                        );
+                        samples.push(sample);
+                    }
+                }
+            }
+        }
+    }
+    report::compute_scaling(&mut samples);
+
+    if let Err(err) = write_reports(&config, &samples) {
+        eprintln!("error: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn workloads(config: &Config, mix: Mix) -> Vec<Workload> {
+    match mix {
+        Mix::ProducerConsumer => config
+            .pairs
+            .iter()
+            .map(|&(producers, consumers)| Workload {
+                mix,
+                producers,
+                consumers,
+            })
+            .collect(),
+        Mix::Mixed(_) => config
+            .threads
+            .iter()
+            .map(|&threads| Workload {
+                mix,
+                producers: threads,
+                consumers: threads,
+            })
+            .collect(),
+    }
+}
+
+// Runs a configuration `config.runs` times and keeps the median.
+fn sample(
+    config: &Config,
+    structure: target::Structure,
+    workload: Workload,
+    payload: usize,
+) -> Option<Sample> {
+    let mut runs = Vec::with_capacity(config.runs);
+    for _ in 0..config.runs {
+        runs.push(run::measure(structure, workload, payload, config.ops)?);
+    }
+    runs.sort_by_key(|measurement| measurement.elapsed);
+    let median = runs[runs.len() / 2];
+    let seconds = median.elapsed.as_secs_f64();
+
+    Some(Sample {
+        structure,
+        mix: workload.mix,
+        producers: workload.producers,
+        consumers: workload.consumers,
+        threads: workload.threads(),
+        payload,
+        ops: median.ops,
+        seconds,
+        ops_per_sec: median.ops as f64 / seconds,
+        scaling: 1.0,
+    })
+}
+
+fn write_reports(config: &Config, samples: &[Sample]) -> io::Result<()> {
+    if let Some(path) = &config.csv {
+        report::write_csv(samples, BufWriter::new(File::create(path)?))?;
+    }
+    if let Some(path) = &config.json {
+        report::write_json(samples, BufWriter::new(File::create(path)?))?;
+    }
+    if config.csv.is_none() && config.json.is_none() {
+        report::write_csv(samples, io::stdout().lock())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::config::Mix;
+use crate::target::Structure;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub structure: Structure,
+    pub mix: Mix,
+    pub producers: usize,
+    pub consumers: usize,
+    pub threads: usize,
+    pub payload: usize,
+    pub ops: usize,
+    pub seconds: f64,
+    pub ops_per_sec: f64,
+    /// `ops_per_sec` relative to the run with the fewest threads for the
+    /// same structure, mix and payload, i.e. one point on a scaling curve.
+    pub scaling: f64,
+}
+
+const COLUMNS: [&str; 10] = [
+    "structure",
+    "mix",
+    "producers",
+    "consumers",
+    "threads",
+    "payload_bytes",
+    "ops",
+    "seconds",
+    "ops_per_sec",
+    "scaling",
+];
+
+/// Fills in `scaling` for every sample.
+pub fn compute_scaling(samples: &mut [Sample]) {
+    for i in 0..samples.len() {
+        let baseline = samples
+            .iter()
+            .filter(|other| {
+                other.structure == samples[i].structure
+                    && other.mix == samples[i].mix
+                    && other.payload == samples[i].payload
+            })
+            .min_by_key(|other| other.threads)
+            .map_or(samples[i].ops_per_sec, |other| other.ops_per_sec);
+        samples[i].scaling = samples[i].ops_per_sec / baseline;
+    }
+}
+
+pub fn write_csv(samples: &[Sample], mut out: impl Write) -> io::Result<()> {
+    writeln!(out, "{}", COLUMNS.join(","))?;
+    for sample in samples {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{:.6},{:.0},{:.3}",
+            sample.structure.name(),
+            sample.mix,
+            sample.producers,
+            sample.consumers,
+            sample.threads,
+            sample.payload,
+            sample.ops,
+            sample.seconds,
+            sample.ops_per_sec,
+            sample.scaling,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn write_json(samples: &[Sample], mut out: impl Write) -> io::Result<()> {
+    let mut json = String::from("[\n");
+    for (i, sample) in samples.iter().enumerate() {
+        let separator = if i + 1 < samples.len() { "," } else { "" };
+        let _ = writeln!(
+            json,
+            "  {{\"structure\": \"{}\", \"mix\": \"{}\", \"producers\": {}, \"consumers\": {}, \
+             \"threads\": {}, \"payload_bytes\": {}, \"ops\": {}, \"seconds\": {:.6}, \
+             \"ops_per_sec\": {:.0}, \"scaling\": {:.3}}}{separator}",
+            sample.structure.name(),
+            sample.mix,
+            sample.producers,
+            sample.consumers,
+            sample.threads,
+            sample.payload,
+            sample.ops,
+            sample.seconds,
+            sample.ops_per_sec,
+            sample.scaling,
+        );
+    }
+    json.push_str("]\n");
+
+    out.write_all(json.as_bytes())
+}
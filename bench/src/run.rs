@@ -0,0 +1,319 @@
+use crate::config::Mix;
+use crate::target::{Bounded, Queue, Stack, Structure, Target};
+use concurrency::{
+    spsc, AsyncQueue, BlockingQueue, BlockingStack, ConcurrentPool, LockFreeQueue, LockFreeStack,
+    RingQueue,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Barrier, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Mixed workloads start from a non-empty structure so early pops have
+// something to take.
+const PREFILL: usize = 1024;
+
+// Slots in the elimination array of `lockfree_stack_elim`.
+const ELIMINATION_SLOTS: usize = 16;
+
+// Capacity of `ring_queue` and `spsc`, well above `PREFILL`.
+const RING_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Workload {
+    pub mix: Mix,
+    pub producers: usize,
+    pub consumers: usize,
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mix {
+            Mix::ProducerConsumer => write!(f, "{}x{}", self.producers, self.consumers),
+            Mix::Mixed(_) => write!(f, "{} threads", self.producers),
+        }
+    }
+}
+
+impl Workload {
+    pub fn threads(&self) -> usize {
+        match self.mix {
+            Mix::ProducerConsumer => self.producers + self.consumers,
+            Mix::Mixed(_) => self.producers,
+        }
+    }
+}
+
+/// How long `ops` operations took, and how many were actually counted.
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub ops: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy)]
+struct Payload<const N: usize>([u8; N]);
+
+impl<const N: usize> Payload<N> {
+    fn new(seed: usize) -> Self {
+        Self([seed as u8; N])
+    }
+}
+
+/// Runs one workload against a fresh instance of `structure`. Returns `None`
+/// for combinations `mpsc` can't express: more than one consumer, or
+/// threads that both push and pop.
+pub fn measure(
+    structure: Structure,
+    workload: Workload,
+    payload: usize,
+    ops: usize,
+) -> Option<Measurement> {
+    match payload {
+        8 => measure_payload::<8>(structure, workload, ops),
+        16 => measure_payload::<16>(structure, workload, ops),
+        32 => measure_payload::<32>(structure, workload, ops),
+        64 => measure_payload::<64>(structure, workload, ops),
+        256 => measure_payload::<256>(structure, workload, ops),
+        1024 => measure_payload::<1024>(structure, workload, ops),
+        4096 => measure_payload::<4096>(structure, workload, ops),
+        _ => unreachable!("payload sizes are checked when parsing"),
+    }
+}
+
+fn measure_payload<const N: usize>(
+    structure: Structure,
+    workload: Workload,
+    ops: usize,
+) -> Option<Measurement> {
+    Some(match structure {
+        Structure::BlockingQueue => run::<N, _>(&Queue(BlockingQueue::new()), workload, ops),
+        Structure::BlockingStack => run::<N, _>(&Stack(BlockingStack::new()), workload, ops),
+        Structure::LockFreeQueue => run::<N, _>(&Queue(LockFreeQueue::new()), workload, ops),
+        Structure::RingQueue => run::<N, _>(&Bounded(RingQueue::new(RING_CAPACITY)), workload, ops),
+        Structure::LockFreeStack => run::<N, _>(&Stack(LockFreeStack::new()), workload, ops),
+        Structure::EliminationStack => run::<N, _>(
+            &Stack(LockFreeStack::new().with_elimination(ELIMINATION_SLOTS)),
+            workload,
+            ops,
+        ),
+        Structure::ConcurrentPool => run::<N, _>(&Queue(ConcurrentPool::new()), workload, ops),
+        Structure::AsyncQueue => run::<N, _>(&Queue(AsyncQueue::new()), workload, ops),
+        Structure::MutexVecDeque => run::<N, _>(&Mutex::new(VecDeque::new()), workload, ops),
+        Structure::Mpsc => return run_mpsc::<N>(workload, ops),
+        Structure::Spsc => return run_spsc::<N>(workload, ops),
+    })
+}
+
+fn run<const N: usize, S: Target<Payload<N>>>(
+    target: &S,
+    workload: Workload,
+    ops: usize,
+) -> Measurement {
+    match workload.mix {
+        Mix::ProducerConsumer => producer_consumer(target, workload, ops),
+        Mix::Mixed(push_percent) => mixed(target, workload.producers, push_percent, ops),
+    }
+}
+
+// Producers push `ops / 2` items between them and consumers pop until all of
+// them have come out. A push and a pop count as one operation each; pops
+// that find the structure empty don't count.
+fn producer_consumer<const N: usize, S: Target<Payload<N>>>(
+    target: &S,
+    workload: Workload,
+    ops: usize,
+) -> Measurement {
+    let per_producer = (ops / 2).div_ceil(workload.producers);
+    let items = per_producer * workload.producers;
+    let consumed = AtomicUsize::new(0);
+    let barrier = Barrier::new(workload.threads() + 1);
+
+    let elapsed = timed(&barrier, |scope| {
+        for _ in 0..workload.producers {
+            scope.spawn(|| {
+                barrier.wait();
+                for i in 0..per_producer {
+                    target.push(Payload::new(i));
+                }
+            });
+        }
+        for _ in 0..workload.consumers {
+            scope.spawn(|| {
+                barrier.wait();
+                while consumed.load(Ordering::Relaxed) < items {
+                    match target.try_pop() {
+                        Some(item) => {
+                            black_box(item);
+                            consumed.fetch_add(1, Ordering::Relaxed);
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            });
+        }
+    });
+
+    Measurement {
+        ops: items * 2,
+        elapsed,
+    }
+}
+
+// Every thread runs its share of `ops`, pushing `push_percent` of the time
+// and popping otherwise. Every attempt counts, including empty pops and
+// pushes that find a bounded structure full. Those can't wait for room,
+// since with a high push percentage nothing may pop often enough to make
+// any.
+fn mixed<const N: usize, S: Target<Payload<N>>>(
+    target: &S,
+    threads: usize,
+    push_percent: u8,
+    ops: usize,
+) -> Measurement {
+    for i in 0..PREFILL {
+        target.push(Payload::new(i));
+    }
+    let per_thread = ops.div_ceil(threads);
+    let barrier = Barrier::new(threads + 1);
+
+    let elapsed = timed(&barrier, |scope| {
+        for seed in 0..threads {
+            let barrier = &barrier;
+            scope.spawn(move || {
+                let mut rng = XorShift::new(seed);
+                barrier.wait();
+                for i in 0..per_thread {
+                    if rng.next_percent() < push_percent {
+                        let _ = black_box(target.try_push(Payload::new(i)));
+                    } else {
+                        black_box(target.try_pop());
+                    }
+                }
+            });
+        }
+    });
+
+    Measurement {
+        ops: per_thread * threads,
+        elapsed,
+    }
+}
+
+fn run_mpsc<const N: usize>(workload: Workload, ops: usize) -> Option<Measurement> {
+    if workload.mix != Mix::ProducerConsumer || workload.consumers != 1 {
+        return None;
+    }
+
+    let per_producer = (ops / 2).div_ceil(workload.producers);
+    let items = per_producer * workload.producers;
+    let (sender, receiver) = mpsc::channel::<Payload<N>>();
+    let barrier = Barrier::new(workload.threads() + 1);
+
+    let elapsed = timed(&barrier, |scope| {
+        for _ in 0..workload.producers {
+            let sender = sender.clone();
+            scope.spawn(|| {
+                let sender = sender;
+                barrier.wait();
+                for i in 0..per_producer {
+                    sender.send(Payload::new(i)).unwrap();
+                }
+            });
+        }
+        scope.spawn(|| {
+            let receiver = receiver;
+            barrier.wait();
+            let mut received = 0;
+            while received < items {
+                match receiver.try_recv() {
+                    Ok(item) => {
+                        black_box(item);
+                        received += 1;
+                    }
+                    Err(_) => thread::yield_now(),
+                }
+            }
+        });
+    });
+
+    Some(Measurement {
+        ops: items * 2,
+        elapsed,
+    })
+}
+
+fn run_spsc<const N: usize>(workload: Workload, ops: usize) -> Option<Measurement> {
+    if workload.mix != Mix::ProducerConsumer || workload.producers != 1 || workload.consumers != 1 {
+        return None;
+    }
+
+    let items = ops / 2;
+    let (mut producer, mut consumer) = spsc::<Payload<N>>(RING_CAPACITY);
+    let barrier = Barrier::new(workload.threads() + 1);
+
+    let elapsed = timed(&barrier, |scope| {
+        scope.spawn(|| {
+            barrier.wait();
+            for i in 0..items {
+                let mut item = Payload::new(i);
+                while let Err(rejected) = producer.try_push(item) {
+                    item = rejected;
+                    thread::yield_now();
+                }
+            }
+        });
+        scope.spawn(|| {
+            barrier.wait();
+            let mut received = 0;
+            while received < items {
+                match consumer.try_pop() {
+                    Some(item) => {
+                        black_box(item);
+                        received += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+        });
+    });
+
+    Some(Measurement {
+        ops: items * 2,
+        elapsed,
+    })
+}
+
+// Spawns the workers through `spawn`, releases them all at once and times
+// until the last one finishes.
+fn timed<'env>(
+    barrier: &'env Barrier,
+    spawn: impl for<'scope> FnOnce(&'scope thread::Scope<'scope, 'env>),
+) -> Duration {
+    let mut start = None;
+    thread::scope(|scope| {
+        spawn(scope);
+        barrier.wait();
+        start = Some(Instant::now());
+    });
+
+    start.unwrap().elapsed()
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: usize) -> Self {
+        Self(0x9E37_79B9_7F4A_7C15 ^ (seed as u64 + 1).wrapping_mul(0xBF58_476D_1CE4_E5B9))
+    }
+
+    fn next_percent(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % 100) as u8
+    }
+}
@@ -0,0 +1,123 @@
+use concurrency::{ConcurrentQueue, ConcurrentStack};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Structure {
+    BlockingQueue,
+    BlockingStack,
+    LockFreeQueue,
+    RingQueue,
+    Spsc,
+    LockFreeStack,
+    EliminationStack,
+    ConcurrentPool,
+    AsyncQueue,
+    Mpsc,
+    MutexVecDeque,
+}
+
+impl Structure {
+    pub const ALL: [Structure; 11] = [
+        Structure::BlockingQueue,
+        Structure::BlockingStack,
+        Structure::LockFreeQueue,
+        Structure::RingQueue,
+        Structure::Spsc,
+        Structure::LockFreeStack,
+        Structure::EliminationStack,
+        Structure::ConcurrentPool,
+        Structure::AsyncQueue,
+        Structure::Mpsc,
+        Structure::MutexVecDeque,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Structure::BlockingQueue => "blocking_queue",
+            Structure::BlockingStack => "blocking_stack",
+            Structure::LockFreeQueue => "lockfree_queue",
+            Structure::RingQueue => "ring_queue",
+            Structure::Spsc => "spsc",
+            Structure::LockFreeStack => "lockfree_stack",
+            Structure::EliminationStack => "lockfree_stack_elim",
+            Structure::ConcurrentPool => "concurrent_pool",
+            Structure::AsyncQueue => "async_queue",
+            Structure::Mpsc => "mpsc",
+            Structure::MutexVecDeque => "mutex_vecdeque",
+        }
+    }
+}
+
+/// The operations every workload is built from. `push` never fails:
+/// unbounded structures always take the item and bounded ones are wrapped in
+/// [`Bounded`], which waits for room. `try_push` only fails on a full
+/// bounded structure, for workloads where nothing may be popping to make
+/// room. `mpsc` and `spsc` don't fit, since their ends can't be shared, and
+/// have their own runners.
+pub trait Target<T>: Sync {
+    fn push(&self, item: T);
+    fn try_pop(&self) -> Option<T>;
+
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.push(item);
+        Ok(())
+    }
+}
+
+pub struct Queue<Q>(pub Q);
+
+impl<T, Q: ConcurrentQueue<T> + Sync> Target<T> for Queue<Q> {
+    fn push(&self, item: T) {
+        assert!(self.0.try_push(item).is_ok(), "unbounded push failed");
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.0.try_pop()
+    }
+}
+
+/// A bounded queue whose pushes yield and retry until there is room, so a
+/// full queue shows up as lost throughput rather than a failed push. Its
+/// `try_push` hands the item back instead.
+pub struct Bounded<Q>(pub Q);
+
+impl<T, Q: ConcurrentQueue<T> + Sync> Target<T> for Bounded<Q> {
+    fn push(&self, mut item: T) {
+        while let Err(rejected) = self.0.try_push(item) {
+            item = rejected;
+            thread::yield_now();
+        }
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.0.try_pop()
+    }
+
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.0.try_push(item)
+    }
+}
+
+pub struct Stack<S>(pub S);
+
+impl<T, S: ConcurrentStack<T> + Sync> Target<T> for Stack<S> {
+    fn push(&self, item: T) {
+        assert!(self.0.try_push(item).is_ok(), "unbounded push failed");
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.0.try_pop()
+    }
+}
+
+impl<T: Send> Target<T> for Mutex<VecDeque<T>> {
+    fn push(&self, item: T) {
+        self.lock().unwrap().push_back(item);
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.lock().unwrap().pop_front()
+    }
+}
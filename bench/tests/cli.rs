@@ -0,0 +1,53 @@
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Runs the bench binary and fails if it hasn't exited within `limit`.
+fn run_within(limit: Duration, args: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bench"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let deadline = Instant::now() + limit;
+    while child.try_wait().unwrap().is_none() {
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("bench {} didn't finish within {limit:?}", args.join(" "));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn push_heavy_mix_on_a_bounded_queue_finishes() {
+    // 90% pushes overflow the 4096-slot ring long before the run ends.
+    let csv = run_within(
+        Duration::from_secs(60),
+        &[
+            "--structures",
+            "ring_queue",
+            "--mix",
+            "90",
+            "--threads",
+            "2",
+            "--ops",
+            "20000",
+            "--payload",
+            "8",
+            "--runs",
+            "1",
+        ],
+    );
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("ring_queue,push90,"));
+}
@@ -1,84 +1,342 @@
+use crate::{PopFuture, PopStream, PushFuture, PushSink};
+use concurrency_core::sync::{Arc, Condvar, Mutex};
+use concurrency_core::{
+    BlockingConcurrentQueue, ConcurrentQueue, PopError, PopTimeoutError, PushError,
+    PushTimeoutError, WakerSet,
+};
 use std::collections::VecDeque;
 use std::fmt;
-use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 pub struct BlockingQueue<T> {
-    queue: Arc<Mutex<VecDeque<T>>>,
-    cvar: Condvar,
+    inner: Arc<Inner<T>>,
+}
+
+// Shared by every clone, so a push through one handle wakes a thread blocked
+// on another. Threads wait on the condvars, tasks in the waker sets, and
+// every change notifies both.
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    pop_wakers: WakerSet,
+    push_wakers: WakerSet,
+    bound: Option<usize>,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    closed: bool,
 }
 
 impl<T> BlockingQueue<T> {
     pub fn new() -> Self {
+        Self::from_parts(VecDeque::new(), None)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_parts(VecDeque::with_capacity(capacity), None)
+    }
+
+    /// Creates a queue that holds at most `bound` items. Once it's full,
+    /// `push` waits for room and `try_push` hands the item back instead.
+    pub fn bounded(bound: usize) -> Self {
+        assert!(
+            bound > 0,
+            "a bounded queue needs room for at least one item"
+        );
+        Self::from_parts(VecDeque::with_capacity(bound), Some(bound))
+    }
+
+    fn from_parts(queue: VecDeque<T>, bound: Option<usize>) -> Self {
         Self {
-            queue: Arc::new(Mutex::new(VecDeque::new())),
-            cvar: Condvar::new(),
+            inner: Arc::new(Inner {
+                state: Mutex::new(State {
+                    queue,
+                    closed: false,
+                }),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+                pop_wakers: WakerSet::new(),
+                push_wakers: WakerSet::new(),
+                bound,
+            }),
         }
     }
 
-    pub fn _with_capacity(capacity: usize) -> Self {
-        Self {
-            queue: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
-            cvar: Condvar::new(),
+    pub fn push(&self, item: T) -> Result<(), PushError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && self.is_full(&state.queue) {
+            state = self.inner.not_full.wait(state).unwrap();
         }
+        if state.closed {
+            return Err(PushError::Closed(item));
+        }
+        state.queue.push_back(item);
+        drop(state);
+        self.notify_not_empty();
+
+        Ok(())
+    }
+
+    /// Hands the item back if the queue is full or closed.
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.closed || self.is_full(&state.queue) {
+            return Err(item);
+        }
+        state.queue.push_back(item);
+        drop(state);
+        self.notify_not_empty();
+
+        Ok(())
     }
 
-    pub fn push(&self, item: T) {
-        let mut queue = self.queue.lock().unwrap();
-        queue.push_back(item);
-        self.cvar.notify_one();
+    /// Like `push`, but gives up and hands the item back if the queue is
+    /// still full after `timeout`.
+    pub fn push_timeout(&self, item: T, timeout: Duration) -> Result<(), PushTimeoutError<T>> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.push_deadline(item, deadline),
+            None => self.push(item).map_err(Into::into),
+        }
     }
 
-    pub fn pop(&self) -> T {
-        let mut queue = self.queue.lock().unwrap();
-        while queue.is_empty() {
-            queue = self.cvar.wait(queue).unwrap();
+    pub fn push_deadline(&self, item: T, deadline: Instant) -> Result<(), PushTimeoutError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && self.is_full(&state.queue) {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PushTimeoutError::Timeout(item));
+            }
+            state = self
+                .inner
+                .not_full
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+        if state.closed {
+            return Err(PushTimeoutError::Closed(item));
         }
-        queue.pop_front().unwrap()
+        state.queue.push_back(item);
+        drop(state);
+        self.notify_not_empty();
+
+        Ok(())
+    }
+
+    /// Waits for an item. Once the queue is closed, the items still in it
+    /// are handed out as usual and `PopError::Closed` follows after them.
+    pub fn pop(&self) -> Result<T, PopError> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && state.queue.is_empty() {
+            state = self.inner.not_empty.wait(state).unwrap();
+        }
+        let item = state.queue.pop_front().ok_or(PopError::Closed)?;
+        drop(state);
+        self.notify_not_full();
+
+        Ok(item)
+    }
+
+    /// Like `pop`, but gives up if nothing arrives within `timeout`.
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.pop_deadline(deadline),
+            None => self.pop().map_err(Into::into),
+        }
+    }
+
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && state.queue.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PopTimeoutError::Timeout);
+            }
+            state = self
+                .inner
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+        let item = state.queue.pop_front().ok_or(PopTimeoutError::Closed)?;
+        drop(state);
+        self.notify_not_full();
+
+        Ok(item)
     }
 
     pub fn try_pop(&self) -> Option<T> {
-        self.queue.lock().unwrap().pop_front()
+        let item = self.inner.state.lock().unwrap().queue.pop_front();
+        if item.is_some() {
+            self.notify_not_full();
+        }
+
+        item
+    }
+
+    /// Closes the queue and wakes every blocked `push` and `pop`.
+    ///
+    /// Pushes fail from then on, while pops keep draining whatever is left.
+    /// Closing twice is a no-op.
+    pub fn close(&self) {
+        self.inner.state.lock().unwrap().closed = true;
+        self.inner.not_empty.notify_all();
+        self.inner.not_full.notify_all();
+        self.inner.pop_wakers.notify_all();
+        self.inner.push_wakers.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.state.lock().unwrap().closed
+    }
+
+    /// Waits for an item without blocking the thread. The returned future
+    /// can be dropped at any point without losing an item or a wakeup.
+    pub fn pop_async(&self) -> PopFuture<'_, T> {
+        PopFuture::new(self)
+    }
+
+    /// Waits for room without blocking the thread. On an unbounded queue
+    /// this is ready straight away. Dropping the future before it completes
+    /// drops `item` without pushing it.
+    pub fn push_async(&self, item: T) -> PushFuture<'_, T> {
+        PushFuture::new(self, item)
+    }
+
+    /// Turns this handle into a `Stream` of popped items that ends once the
+    /// queue is closed and drained.
+    pub fn into_stream(self) -> PopStream<T> {
+        PopStream::new(self)
+    }
+
+    /// Turns this handle into a `Sink` that pushes into the queue, waiting
+    /// for room if it's bounded. Closing the sink closes the queue.
+    pub fn into_sink(self) -> PushSink<T> {
+        PushSink::new(self)
+    }
+
+    pub(crate) fn poll_pop(
+        &self,
+        cx: &mut Context<'_>,
+        key: &mut Option<usize>,
+    ) -> Poll<Result<T, PopError>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if let Some(item) = state.queue.pop_front() {
+            drop(state);
+            self.inner.pop_wakers.remove(key);
+            self.notify_not_full();
+            return Poll::Ready(Ok(item));
+        }
+        if state.closed {
+            drop(state);
+            self.inner.pop_wakers.remove(key);
+            return Poll::Ready(Err(PopError::Closed));
+        }
+        // Registered under the lock, so a push can't slip in between the
+        // check above and the registration.
+        self.inner.pop_wakers.register(key, cx.waker());
+
+        Poll::Pending
+    }
+
+    pub(crate) fn poll_push(
+        &self,
+        cx: &mut Context<'_>,
+        item: &mut Option<T>,
+        key: &mut Option<usize>,
+    ) -> Poll<Result<(), PushError<T>>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.closed {
+            drop(state);
+            self.inner.push_wakers.remove(key);
+            let item = item.take().expect("polled after completion");
+            return Poll::Ready(Err(PushError::Closed(item)));
+        }
+        if !self.is_full(&state.queue) {
+            state
+                .queue
+                .push_back(item.take().expect("polled after completion"));
+            drop(state);
+            self.inner.push_wakers.remove(key);
+            self.notify_not_empty();
+            return Poll::Ready(Ok(()));
+        }
+        self.inner.push_wakers.register(key, cx.waker());
+
+        Poll::Pending
+    }
+
+    pub(crate) fn cancel_pop(&self, key: &mut Option<usize>) {
+        self.inner.pop_wakers.cancel(key);
+    }
+
+    pub(crate) fn cancel_push(&self, key: &mut Option<usize>) {
+        self.inner.push_wakers.cancel(key);
+    }
+
+    fn notify_not_empty(&self) {
+        self.inner.not_empty.notify_one();
+        self.inner.pop_wakers.notify_one();
+    }
+
+    fn notify_not_full(&self) {
+        self.inner.not_full.notify_one();
+        self.inner.push_wakers.notify_one();
+    }
+
+    fn is_full(&self, queue: &VecDeque<T>) -> bool {
+        self.inner.bound.is_some_and(|bound| queue.len() >= bound)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.queue.lock().unwrap().is_empty()
+        self.inner.state.lock().unwrap().queue.is_empty()
     }
 
     pub fn len(&self) -> usize {
-        self.queue.lock().unwrap().len()
+        self.inner.state.lock().unwrap().queue.len()
     }
 
     pub fn peek(&self) -> Option<T>
     where
         T: Clone,
     {
-        self.queue.lock().unwrap().front().cloned()
+        self.inner.state.lock().unwrap().queue.front().cloned()
     }
 
     pub fn clear(&self) {
-        self.queue.lock().unwrap().clear();
+        self.inner.state.lock().unwrap().queue.clear();
+        self.inner.not_full.notify_all();
+        self.inner.push_wakers.notify_all();
     }
 
     pub fn drain(&self) -> Vec<T> {
-        self.queue.lock().unwrap().drain(..).collect()
+        let items = self.inner.state.lock().unwrap().queue.drain(..).collect();
+        self.inner.not_full.notify_all();
+        self.inner.push_wakers.notify_all();
+
+        items
     }
 
     pub fn capacity(&self) -> usize {
-        self.queue.lock().unwrap().capacity()
+        self.inner.state.lock().unwrap().queue.capacity()
     }
 
     pub fn contains(&self, item: &T) -> bool
     where
         T: PartialEq,
     {
-        self.queue.lock().unwrap().contains(item)
+        self.inner.state.lock().unwrap().queue.contains(item)
     }
 
     pub fn reverse(&self) -> VecDeque<T>
     where
         T: Clone,
     {
-        let mut queue = self.queue.lock().unwrap().clone();
+        let mut queue = self.inner.state.lock().unwrap().queue.clone();
         queue.make_contiguous().reverse();
 
         queue
@@ -94,8 +352,7 @@ impl<T> Default for BlockingQueue<T> {
 impl<T> Clone for BlockingQueue<T> {
     fn clone(&self) -> Self {
         Self {
-            queue: Arc::clone(&self.queue),
-            cvar: Condvar::new(),
+            inner: Arc::clone(&self.inner),
         }
     }
 }
@@ -105,19 +362,46 @@ where
     T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let queue = self.queue.lock().unwrap();
+        let state = self.inner.state.lock().unwrap();
         f.debug_struct("BlockingQueue")
-            .field("queue", &*queue)
+            .field("queue", &state.queue)
+            .field("bound", &self.inner.bound)
+            .field("closed", &state.closed)
             .finish()
     }
 }
 
 impl<T> From<Vec<T>> for BlockingQueue<T> {
     fn from(v: Vec<T>) -> Self {
-        Self {
-            queue: Arc::new(Mutex::new(VecDeque::from(v))),
-            cvar: Condvar::new(),
-        }
+        Self::from_parts(VecDeque::from(v), None)
+    }
+}
+
+impl<T> ConcurrentQueue<T> for BlockingQueue<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.try_push(item)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> BlockingConcurrentQueue<T> for BlockingQueue<T> {
+    fn push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push(item)
+    }
+
+    fn pop(&self) -> Result<T, PopError> {
+        self.pop()
     }
 }
 
@@ -0,0 +1,169 @@
+use crate::BlockingQueue;
+use concurrency_core::{PopError, PushError};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// Every type here keeps the key of its waker registration between polls and
+// cancels it on drop. A registration that was already notified is passed on
+// to the next waiting task, so dropping a future never strands an item.
+
+/// Future returned by [`BlockingQueue::pop_async`].
+pub struct PopFuture<'a, T> {
+    queue: &'a BlockingQueue<T>,
+    key: Option<usize>,
+}
+
+impl<'a, T> PopFuture<'a, T> {
+    pub(crate) fn new(queue: &'a BlockingQueue<T>) -> Self {
+        Self { queue, key: None }
+    }
+}
+
+impl<T> Future for PopFuture<'_, T> {
+    type Output = Result<T, PopError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.queue.poll_pop(cx, &mut this.key)
+    }
+}
+
+impl<T> Drop for PopFuture<'_, T> {
+    fn drop(&mut self) {
+        self.queue.cancel_pop(&mut self.key);
+    }
+}
+
+/// Future returned by [`BlockingQueue::push_async`].
+pub struct PushFuture<'a, T> {
+    queue: &'a BlockingQueue<T>,
+    item: Option<T>,
+    key: Option<usize>,
+}
+
+impl<'a, T> PushFuture<'a, T> {
+    pub(crate) fn new(queue: &'a BlockingQueue<T>, item: T) -> Self {
+        Self {
+            queue,
+            item: Some(item),
+            key: None,
+        }
+    }
+}
+
+// The item is only ever moved out whole, never pinned.
+impl<T> Unpin for PushFuture<'_, T> {}
+
+impl<T> Future for PushFuture<'_, T> {
+    type Output = Result<(), PushError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.queue.poll_push(cx, &mut this.item, &mut this.key)
+    }
+}
+
+impl<T> Drop for PushFuture<'_, T> {
+    fn drop(&mut self) {
+        self.queue.cancel_push(&mut self.key);
+    }
+}
+
+/// `Stream` returned by [`BlockingQueue::into_stream`].
+pub struct PopStream<T> {
+    queue: BlockingQueue<T>,
+    key: Option<usize>,
+}
+
+impl<T> PopStream<T> {
+    pub(crate) fn new(queue: BlockingQueue<T>) -> Self {
+        Self { queue, key: None }
+    }
+}
+
+impl<T> Stream for PopStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.queue
+            .poll_pop(cx, &mut this.key)
+            .map(|result| result.ok())
+    }
+}
+
+impl<T> Drop for PopStream<T> {
+    fn drop(&mut self) {
+        self.queue.cancel_pop(&mut self.key);
+    }
+}
+
+/// `Sink` returned by [`BlockingQueue::into_sink`].
+///
+/// `start_send` parks the item in the sink and the push happens on the next
+/// `poll_ready` or `poll_flush`, so a full queue applies backpressure without
+/// losing items.
+pub struct PushSink<T> {
+    queue: BlockingQueue<T>,
+    pending: Option<T>,
+    key: Option<usize>,
+}
+
+impl<T> PushSink<T> {
+    pub(crate) fn new(queue: BlockingQueue<T>) -> Self {
+        Self {
+            queue,
+            pending: None,
+            key: None,
+        }
+    }
+}
+
+impl<T> Unpin for PushSink<T> {}
+
+impl<T> Sink<T> for PushSink<T> {
+    type Error = PushError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        assert!(
+            this.pending.is_none(),
+            "start_send called without poll_ready"
+        );
+        this.pending = Some(item);
+
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.pending.is_none() {
+            return Poll::Ready(Ok(()));
+        }
+
+        this.queue.poll_push(cx, &mut this.pending, &mut this.key)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(())) => {
+                self.queue.close();
+                Poll::Ready(Ok(()))
+            }
+            other => other,
+        }
+    }
+}
+
+impl<T> Drop for PushSink<T> {
+    fn drop(&mut self) {
+        self.queue.cancel_push(&mut self.key);
+    }
+}
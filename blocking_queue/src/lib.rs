@@ -0,0 +1,11 @@
+mod blocking_queue;
+mod channel;
+mod future;
+
+pub use blocking_queue::BlockingQueue;
+pub use channel::{
+    bounded, channel, Iter, Receiver, RecvError, RecvTimeoutError, SendError, SendTimeoutError,
+    Sender, TryRecvError, TrySendError,
+};
+pub use concurrency_core::{PopError, PopTimeoutError, PushError, PushTimeoutError};
+pub use future::{PopFuture, PopStream, PushFuture, PushSink};
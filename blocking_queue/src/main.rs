@@ -1,7 +1,12 @@
-mod blocking_queue;
-
-use blocking_queue::BlockingQueue;
-use std::{sync::Arc, thread, time::Duration};
+use blocking_queue::{
+    BlockingQueue, PopError, PopTimeoutError, PushError, PushTimeoutError, SendError, TryRecvError,
+    TrySendError,
+};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 fn main() {
     println!("Testing Single-threaded BlockingQueue...");
@@ -9,13 +14,13 @@ fn main() {
     let queue = BlockingQueue::new();
     println!("Created new queue: {:?}", queue);
 
-    queue.push(1);
-    queue.push(2);
-    queue.push(3);
+    queue.push(1).unwrap();
+    queue.push(2).unwrap();
+    queue.push(3).unwrap();
     println!("After pushing 1, 2, 3: {:?}", queue);
 
-    assert_eq!(queue.pop(), 1);
-    assert_eq!(queue.pop(), 2);
+    assert_eq!(queue.pop(), Ok(1));
+    assert_eq!(queue.pop(), Ok(2));
     println!("After popping twice: {:?}", queue);
 
     assert_eq!(queue.try_pop(), Some(3));
@@ -30,7 +35,7 @@ fn main() {
         queue.len()
     );
 
-    queue.push(4);
+    queue.push(4).unwrap();
     assert_eq!(queue.peek(), Some(4));
     println!("Peeked: {:?}", queue.peek());
 
@@ -38,17 +43,17 @@ fn main() {
     assert!(queue.is_empty());
     println!("Cleared queue: {:?}", queue);
 
-    queue.push(5);
-    queue.push(6);
-    queue.push(7);
+    queue.push(5).unwrap();
+    queue.push(6).unwrap();
+    queue.push(7).unwrap();
     let drained = queue.drain();
     assert_eq!(drained, vec![5, 6, 7]);
     assert!(queue.is_empty());
     println!("Drained: {:?}, Queue now: {:?}", drained, queue);
     println!("Capacity: {}", queue.capacity());
 
-    queue.push(8);
-    queue.push(9);
+    queue.push(8).unwrap();
+    queue.push(9).unwrap();
     assert!(queue.contains(&8));
     assert!(!queue.contains(&10));
     println!(
@@ -74,15 +79,15 @@ fn main() {
 
     let producer = thread::spawn(move || {
         for i in 0..5 {
-            queue_clone.push(i);
+            queue_clone.push(i).unwrap();
             println!("Produced: {}", i);
             thread::sleep(Duration::from_millis(100));
         }
+        queue_clone.close();
     });
 
     let consumer = thread::spawn(move || {
-        for _ in 0..5 {
-            let item = queue.pop();
+        while let Ok(item) = queue.pop() {
             println!("Consumed: {}", item);
             thread::sleep(Duration::from_millis(150));
         }
@@ -93,18 +98,117 @@ fn main() {
 
     println!("Multi-threaded test completed.");
 
+    println!("Testing bounded BlockingQueue");
+    let queue = Arc::new(BlockingQueue::bounded(2));
+    queue.push(1).unwrap();
+    queue.push(2).unwrap();
+    assert_eq!(queue.try_push(3), Err(3));
+    println!("Full queue rejected try_push: {:?}", queue);
+
+    let queue_clone = Arc::clone(&queue);
+    let blocked_producer = thread::spawn(move || {
+        queue_clone.push(3).unwrap();
+        println!("Producer unblocked");
+    });
+
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.pop(), Ok(1));
+    blocked_producer.join().unwrap();
+    assert_eq!(queue.try_pop(), Some(2));
+    assert_eq!(queue.try_pop(), Some(3));
+    println!("Bounded test completed.");
+
+    println!("Testing timed waits");
+    let queue = BlockingQueue::bounded(1);
+    assert_eq!(
+        queue.pop_timeout(Duration::from_millis(50)),
+        Err(PopTimeoutError::Timeout)
+    );
+    queue.push(1).unwrap();
+    assert_eq!(
+        queue.push_timeout(2, Duration::from_millis(50)),
+        Err(PushTimeoutError::Timeout(2))
+    );
+    assert_eq!(queue.pop_timeout(Duration::from_millis(50)), Ok(1));
+    assert_eq!(queue.push_timeout(2, Duration::from_millis(50)), Ok(()));
+    assert_eq!(
+        queue.pop_deadline(Instant::now() + Duration::from_millis(50)),
+        Ok(2)
+    );
+    println!("Timed waits completed.");
+
+    println!("Testing close");
+    let queue = Arc::new(BlockingQueue::<i32>::new());
+    let queue_clone = Arc::clone(&queue);
+    let blocked_consumer = thread::spawn(move || queue_clone.pop());
+
+    thread::sleep(Duration::from_millis(100));
+    queue.close();
+    assert_eq!(blocked_consumer.join().unwrap(), Err(PopError::Closed));
+
+    let queue = BlockingQueue::new();
+    queue.push(1).unwrap();
+    queue.push(2).unwrap();
+    queue.close();
+    assert!(queue.is_closed());
+    assert_eq!(queue.push(3), Err(PushError::Closed(3)));
+    assert_eq!(queue.try_push(3), Err(3));
+    let mut remaining = Vec::new();
+    while let Ok(item) = queue.pop() {
+        remaining.push(item);
+    }
+    assert_eq!(remaining, vec![1, 2]);
+    assert_eq!(
+        queue.pop_timeout(Duration::from_millis(50)),
+        Err(PopTimeoutError::Closed)
+    );
+    println!("Close test completed.");
+
+    println!("Testing channel");
+    let (tx, rx) = blocking_queue::channel();
+    let producers: Vec<_> = (0..2)
+        .map(|id| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..3 {
+                    tx.send(id * 10 + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    assert_eq!(tx.sender_count(), 3);
+    drop(tx);
+
+    let mut received: Vec<_> = rx.iter().collect();
+    received.sort();
+    assert_eq!(received, vec![0, 1, 2, 10, 11, 12]);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    let (tx, rx) = blocking_queue::bounded(1);
+    tx.send(1).unwrap();
+    assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(rx.recv(), Ok(1));
+    drop(rx);
+    assert_eq!(tx.receiver_count(), 0);
+    assert_eq!(tx.send(2), Err(SendError(2)));
+    println!("Channel test completed.");
+
     let queue = Arc::new(BlockingQueue::new());
     let queue_clone = Arc::clone(&queue);
 
     let blocking_thread = thread::spawn(move || {
         println!("Waiting for item...");
-        let item = queue_clone.pop();
+        let item = queue_clone.pop().unwrap();
         println!("Received item: {}", item);
     });
 
     thread::sleep(Duration::from_secs(1));
     println!("Pushing item to unblock thread");
-    queue.push(42);
+    queue.push(42).unwrap();
 
     blocking_thread.join().unwrap();
 }
@@ -0,0 +1,189 @@
+use blocking_queue::{BlockingQueue, PopError, PushError};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+// Just enough executor to drive one future on the current thread.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl CountingWaker {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+    let counter = Arc::new(CountingWaker::default());
+    (Arc::clone(&counter), Waker::from(counter))
+}
+
+#[test]
+fn pop_async_waits_for_push_from_thread() {
+    let queue = BlockingQueue::new();
+    let producer_queue = queue.clone();
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        producer_queue.push(1).unwrap();
+    });
+
+    assert_eq!(block_on(queue.pop_async()), Ok(1));
+    producer.join().unwrap();
+}
+
+#[test]
+fn push_async_waits_for_room() {
+    let queue = BlockingQueue::bounded(1);
+    queue.push(1).unwrap();
+    let consumer_queue = queue.clone();
+    let consumer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        consumer_queue.pop()
+    });
+
+    assert_eq!(block_on(queue.push_async(2)), Ok(()));
+    assert_eq!(consumer.join().unwrap(), Ok(1));
+    assert_eq!(queue.try_pop(), Some(2));
+}
+
+#[test]
+fn dropped_pop_passes_its_wakeup_on() {
+    let queue = BlockingQueue::new();
+    let (first_count, first_waker) = counting_waker();
+    let (second_count, second_waker) = counting_waker();
+    let mut first = Box::pin(queue.pop_async());
+    let mut second = Box::pin(queue.pop_async());
+
+    assert!(first
+        .as_mut()
+        .poll(&mut Context::from_waker(&first_waker))
+        .is_pending());
+    assert!(second
+        .as_mut()
+        .poll(&mut Context::from_waker(&second_waker))
+        .is_pending());
+
+    queue.push(1).unwrap();
+    assert_eq!((first_count.count(), second_count.count()), (1, 0));
+
+    drop(first);
+    assert_eq!(second_count.count(), 1);
+    assert_eq!(
+        second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker)),
+        Poll::Ready(Ok(1))
+    );
+}
+
+#[test]
+fn dropped_pending_pop_leaves_nothing_registered() {
+    let queue = BlockingQueue::new();
+    let (count, waker) = counting_waker();
+    let mut pop = Box::pin(queue.pop_async());
+    assert!(pop
+        .as_mut()
+        .poll(&mut Context::from_waker(&waker))
+        .is_pending());
+    drop(pop);
+
+    queue.push(1).unwrap();
+    assert_eq!(count.count(), 0);
+    assert_eq!(queue.try_pop(), Some(1));
+}
+
+#[test]
+fn close_wakes_pending_pop() {
+    let queue = BlockingQueue::<i32>::new();
+    let (count, waker) = counting_waker();
+    let mut pop = Box::pin(queue.pop_async());
+    let mut cx = Context::from_waker(&waker);
+    assert!(pop.as_mut().poll(&mut cx).is_pending());
+
+    queue.close();
+    assert_eq!(count.count(), 1);
+    assert_eq!(
+        pop.as_mut().poll(&mut cx),
+        Poll::Ready(Err(PopError::Closed))
+    );
+}
+
+#[test]
+fn stream_yields_until_closed_and_drained() {
+    let queue = BlockingQueue::new();
+    let mut stream = queue.clone().into_stream();
+    let producer = thread::spawn(move || {
+        for i in 0..3 {
+            queue.push(i).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+        queue.close();
+    });
+
+    let mut items = Vec::new();
+    while let Some(item) = block_on(poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))) {
+        items.push(item);
+    }
+    producer.join().unwrap();
+
+    assert_eq!(items, vec![0, 1, 2]);
+}
+
+#[test]
+fn sink_applies_backpressure() {
+    let queue = BlockingQueue::bounded(1);
+    let mut sink = queue.clone().into_sink();
+    let (count, waker) = counting_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(Pin::new(&mut sink).poll_ready(&mut cx), Poll::Ready(Ok(())));
+    Pin::new(&mut sink).start_send(1).unwrap();
+    assert_eq!(Pin::new(&mut sink).poll_ready(&mut cx), Poll::Ready(Ok(())));
+    Pin::new(&mut sink).start_send(2).unwrap();
+    assert!(Pin::new(&mut sink).poll_flush(&mut cx).is_pending());
+
+    assert_eq!(queue.pop(), Ok(1));
+    assert_eq!(count.count(), 1);
+    assert_eq!(Pin::new(&mut sink).poll_close(&mut cx), Poll::Ready(Ok(())));
+    assert!(queue.is_closed());
+    assert_eq!(queue.pop(), Ok(2));
+
+    let mut sink = queue.into_sink();
+    Pin::new(&mut sink).start_send(3).unwrap();
+    assert_eq!(
+        Pin::new(&mut sink).poll_flush(&mut cx),
+        Poll::Ready(Err(PushError::Closed(3)))
+    );
+}
@@ -0,0 +1,82 @@
+use blocking_queue::{bounded, channel, RecvError, SendError, TryRecvError, TrySendError};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// Results from blocked threads come back over a std channel so a missed
+// wakeup fails the test instead of hanging it.
+const WAKEUP: Duration = Duration::from_secs(5);
+
+#[test]
+fn dropping_every_sender_disconnects_after_the_queued_values() {
+    let (sender, receiver) = channel();
+    let second = sender.clone();
+    sender.send(1).unwrap();
+    second.send(2).unwrap();
+
+    drop(sender);
+    assert!(!receiver.is_disconnected());
+    assert_eq!(receiver.try_recv(), Ok(1));
+
+    drop(second);
+    assert!(receiver.is_disconnected());
+    assert_eq!(receiver.recv(), Ok(2));
+    assert_eq!(receiver.recv(), Err(RecvError::Disconnected));
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn dropping_every_receiver_fails_sends_with_the_value() {
+    let (sender, receiver) = bounded(1);
+    let second = receiver.clone();
+    drop(receiver);
+    assert_eq!(sender.send(1), Ok(()));
+    assert_eq!(second.recv(), Ok(1));
+
+    drop(second);
+    assert!(sender.is_disconnected());
+    assert_eq!(sender.send(2), Err(SendError(2)));
+    assert_eq!(sender.try_send(3), Err(TrySendError::Disconnected(3)));
+}
+
+#[test]
+fn dropping_every_receiver_wakes_a_blocked_send() {
+    let (sender, receiver) = bounded(1);
+    sender.send(1).unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(sender.send(2)).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    drop(receiver);
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Err(SendError(2))));
+}
+
+#[test]
+fn clones_and_drops_are_counted_on_both_sides() {
+    let (sender, receiver) = channel::<u8>();
+    assert_eq!((sender.sender_count(), sender.receiver_count()), (1, 1));
+
+    let senders: Vec<_> = (0..3).map(|_| sender.clone()).collect();
+    let second = receiver.clone();
+    assert_eq!(receiver.sender_count(), 4);
+    assert_eq!(sender.receiver_count(), 2);
+
+    drop(senders);
+    drop(second);
+    assert_eq!(receiver.sender_count(), 1);
+    assert_eq!(sender.receiver_count(), 1);
+    assert!(!receiver.is_disconnected());
+}
+
+#[test]
+fn send_wakes_a_blocked_recv() {
+    let (sender, receiver) = channel();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(receiver.recv()).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    sender.send(7).unwrap();
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Ok(7)));
+}
@@ -0,0 +1,49 @@
+use blocking_queue::BlockingQueue;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// A waiter parked on one clone must be woken by an operation on another. The
+// result comes back over a channel so a regression fails instead of hanging.
+const WAKEUP: Duration = Duration::from_secs(5);
+
+#[test]
+fn push_through_clone_wakes_blocked_pop() {
+    let queue = BlockingQueue::new();
+    let consumer_queue = queue.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(consumer_queue.pop()).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    queue.clone().push(7).unwrap();
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Ok(7)));
+}
+
+#[test]
+fn pop_through_clone_wakes_blocked_push() {
+    let queue = BlockingQueue::bounded(1);
+    queue.push(1).unwrap();
+    let producer_queue = queue.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(producer_queue.push(2)).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(queue.clone().pop(), Ok(1));
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Ok(())));
+    assert_eq!(queue.pop(), Ok(2));
+}
+
+#[test]
+fn close_through_clone_wakes_blocked_pop() {
+    let queue = BlockingQueue::<i32>::new();
+    let consumer_queue = queue.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(consumer_queue.pop().is_err()).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    queue.clone().close();
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(true));
+}
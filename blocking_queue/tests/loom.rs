@@ -0,0 +1,71 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p blocking_queue --release --test loom`.
+use blocking_queue::BlockingQueue;
+use loom::thread;
+
+#[test]
+fn pop_is_woken_by_push() {
+    loom::model(|| {
+        let queue = BlockingQueue::new();
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.pop())
+        };
+
+        queue.push(1).unwrap();
+        assert_eq!(consumer.join().unwrap(), Ok(1));
+    });
+}
+
+#[test]
+fn push_into_full_queue_is_woken_by_pop() {
+    loom::model(|| {
+        let queue = BlockingQueue::bounded(1);
+        queue.push(1).unwrap();
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.push(2))
+        };
+
+        assert_eq!(queue.pop(), Ok(1));
+        assert_eq!(producer.join().unwrap(), Ok(()));
+        assert_eq!(queue.try_pop(), Some(2));
+    });
+}
+
+#[test]
+fn racing_pushes_keep_each_producer_in_order() {
+    loom::model(|| {
+        let queue = BlockingQueue::new();
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                queue.push(1).unwrap();
+                queue.push(2).unwrap();
+            })
+        };
+
+        queue.push(3).unwrap();
+        producer.join().unwrap();
+        let values = queue.drain();
+        let ones = values.iter().position(|&v| v == 1).unwrap();
+        let twos = values.iter().position(|&v| v == 2).unwrap();
+        assert!(ones < twos);
+        assert_eq!(values.len(), 3);
+    });
+}
+
+#[test]
+fn close_wakes_blocked_pop() {
+    loom::model(|| {
+        let queue = BlockingQueue::<i32>::new();
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.pop())
+        };
+
+        queue.close();
+        assert!(consumer.join().unwrap().is_err());
+    });
+}
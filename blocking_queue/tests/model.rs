@@ -0,0 +1,78 @@
+use blocking_queue::BlockingQueue;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::collections::VecDeque;
+
+// Runs random single-threaded operation sequences against the queue and a
+// `VecDeque`, checking every result. proptest shrinks a failing sequence
+// down to the shortest one that still fails. Values are drawn from a small
+// range so `contains` hits as often as it misses.
+#[derive(Debug, Clone)]
+enum Op {
+    TryPush(u8),
+    TryPop,
+    Peek,
+    Contains(u8),
+    Reverse,
+    Drain,
+    Clear,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (0..8u8).prop_map(Op::TryPush),
+        3 => Just(Op::TryPop),
+        1 => Just(Op::Peek),
+        2 => (0..8u8).prop_map(Op::Contains),
+        1 => Just(Op::Reverse),
+        1 => Just(Op::Drain),
+        1 => Just(Op::Clear),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn blocking_queue_matches_vecdeque(
+        bound in proptest::option::of(1..8usize),
+        ops in vec(op(), 0..64),
+    ) {
+        let queue = match bound {
+            Some(bound) => BlockingQueue::bounded(bound),
+            None => BlockingQueue::new(),
+        };
+        let mut model = VecDeque::new();
+        for op in ops {
+            match op {
+                Op::TryPush(value) => {
+                    let fits = bound.is_none_or(|bound| model.len() < bound);
+                    let expected = if fits {
+                        model.push_back(value);
+                        Ok(())
+                    } else {
+                        Err(value)
+                    };
+                    prop_assert_eq!(queue.try_push(value), expected);
+                }
+                Op::TryPop => prop_assert_eq!(queue.try_pop(), model.pop_front()),
+                Op::Peek => prop_assert_eq!(queue.peek(), model.front().copied()),
+                Op::Contains(value) => {
+                    prop_assert_eq!(queue.contains(&value), model.contains(&value));
+                }
+                Op::Reverse => {
+                    let expected: VecDeque<_> = model.iter().rev().copied().collect();
+                    prop_assert_eq!(queue.reverse(), expected);
+                }
+                Op::Drain => {
+                    let expected: Vec<_> = model.drain(..).collect();
+                    prop_assert_eq!(queue.drain(), expected);
+                }
+                Op::Clear => {
+                    queue.clear();
+                    model.clear();
+                }
+            }
+            prop_assert_eq!(queue.len(), model.len());
+            prop_assert_eq!(queue.is_empty(), model.is_empty());
+        }
+    }
+}
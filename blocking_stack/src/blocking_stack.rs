@@ -1,86 +1,234 @@
+use concurrency_core::sync::{Arc, Condvar, Mutex};
+use concurrency_core::{
+    BlockingConcurrentStack, ConcurrentStack, PopError, PopTimeoutError, PushError,
+    PushTimeoutError,
+};
 use std::collections::VecDeque;
 use std::fmt;
-use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 pub struct BlockingStack<T> {
-    stack: Arc<Mutex<VecDeque<T>>>,
-    cvar: Condvar,
+    inner: Arc<Inner<T>>,
+}
+
+// Shared by every clone, so a push through one handle wakes a thread blocked
+// on another.
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    bound: Option<usize>,
+}
+
+struct State<T> {
+    stack: VecDeque<T>,
+    closed: bool,
 }
 
 impl<T> BlockingStack<T> {
     pub fn new() -> Self {
+        Self::from_parts(VecDeque::new(), None)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_parts(VecDeque::with_capacity(capacity), None)
+    }
+
+    /// Creates a stack that holds at most `bound` items. Once it's full,
+    /// `push` waits for room and `try_push` hands the item back instead.
+    pub fn bounded(bound: usize) -> Self {
+        assert!(
+            bound > 0,
+            "a bounded stack needs room for at least one item"
+        );
+        Self::from_parts(VecDeque::with_capacity(bound), Some(bound))
+    }
+
+    fn from_parts(stack: VecDeque<T>, bound: Option<usize>) -> Self {
         Self {
-            stack: Arc::new(Mutex::new(VecDeque::new())),
-            cvar: Condvar::new(),
+            inner: Arc::new(Inner {
+                state: Mutex::new(State {
+                    stack,
+                    closed: false,
+                }),
+                not_empty: Condvar::new(),
+                not_full: Condvar::new(),
+                bound,
+            }),
         }
     }
 
-    pub fn _with_capacity(capacity: usize) -> Self {
-        Self {
-            stack: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
-            cvar: Condvar::new(),
+    pub fn push(&self, item: T) -> Result<(), PushError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && self.is_full(&state.stack) {
+            state = self.inner.not_full.wait(state).unwrap();
+        }
+        if state.closed {
+            return Err(PushError::Closed(item));
         }
+        state.stack.push_back(item);
+        self.inner.not_empty.notify_one();
+
+        Ok(())
     }
 
-    pub fn push(&self, item: T) {
-        let mut stack = self.stack.lock().unwrap();
-        stack.push_back(item);
-        self.cvar.notify_one();
+    /// Hands the item back if the stack is full or closed.
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.closed || self.is_full(&state.stack) {
+            return Err(item);
+        }
+        state.stack.push_back(item);
+        self.inner.not_empty.notify_one();
+
+        Ok(())
     }
 
-    pub fn pop(&self) -> T {
-        let mut stack = self.stack.lock().unwrap();
-        while stack.is_empty() {
-            stack = self.cvar.wait(stack).unwrap();
+    /// Like `push`, but gives up and hands the item back if the stack is
+    /// still full after `timeout`.
+    pub fn push_timeout(&self, item: T, timeout: Duration) -> Result<(), PushTimeoutError<T>> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.push_deadline(item, deadline),
+            None => self.push(item).map_err(Into::into),
         }
-        stack.pop_back().unwrap()
+    }
+
+    pub fn push_deadline(&self, item: T, deadline: Instant) -> Result<(), PushTimeoutError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && self.is_full(&state.stack) {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PushTimeoutError::Timeout(item));
+            }
+            state = self
+                .inner
+                .not_full
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+        if state.closed {
+            return Err(PushTimeoutError::Closed(item));
+        }
+        state.stack.push_back(item);
+        self.inner.not_empty.notify_one();
+
+        Ok(())
+    }
+
+    /// Waits for an item. Once the stack is closed, the items still in it
+    /// are handed out as usual and `PopError::Closed` follows after them.
+    pub fn pop(&self) -> Result<T, PopError> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && state.stack.is_empty() {
+            state = self.inner.not_empty.wait(state).unwrap();
+        }
+        let item = state.stack.pop_back().ok_or(PopError::Closed)?;
+        self.inner.not_full.notify_one();
+
+        Ok(item)
+    }
+
+    /// Like `pop`, but gives up if nothing arrives within `timeout`.
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.pop_deadline(deadline),
+            None => self.pop().map_err(Into::into),
+        }
+    }
+
+    pub fn pop_deadline(&self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        let mut state = self.inner.state.lock().unwrap();
+        while !state.closed && state.stack.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PopTimeoutError::Timeout);
+            }
+            state = self
+                .inner
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+        let item = state.stack.pop_back().ok_or(PopTimeoutError::Closed)?;
+        self.inner.not_full.notify_one();
+
+        Ok(item)
     }
 
     pub fn try_pop(&self) -> Option<T> {
-        self.stack.lock().unwrap().pop_back()
+        let item = self.inner.state.lock().unwrap().stack.pop_back();
+        if item.is_some() {
+            self.inner.not_full.notify_one();
+        }
+
+        item
+    }
+
+    /// Closes the stack and wakes every blocked `push` and `pop`.
+    ///
+    /// Pushes fail from then on, while pops keep draining whatever is left.
+    /// Closing twice is a no-op.
+    pub fn close(&self) {
+        self.inner.state.lock().unwrap().closed = true;
+        self.inner.not_empty.notify_all();
+        self.inner.not_full.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.state.lock().unwrap().closed
+    }
+
+    fn is_full(&self, stack: &VecDeque<T>) -> bool {
+        self.inner.bound.is_some_and(|bound| stack.len() >= bound)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.stack.lock().unwrap().is_empty()
+        self.inner.state.lock().unwrap().stack.is_empty()
     }
 
     pub fn len(&self) -> usize {
-        self.stack.lock().unwrap().len()
+        self.inner.state.lock().unwrap().stack.len()
     }
 
     pub fn peek(&self) -> Option<T>
     where
         T: Clone,
     {
-        self.stack.lock().unwrap().back().cloned()
+        self.inner.state.lock().unwrap().stack.back().cloned()
     }
 
     pub fn clear(&self) {
-        self.stack.lock().unwrap().clear();
+        self.inner.state.lock().unwrap().stack.clear();
+        self.inner.not_full.notify_all();
     }
 
     pub fn drain(&self) -> Vec<T> {
-        self.stack.lock().unwrap().drain(..).collect()
+        let items = self.inner.state.lock().unwrap().stack.drain(..).collect();
+        self.inner.not_full.notify_all();
+
+        items
     }
 
     pub fn capacity(&self) -> usize {
-        self.stack.lock().unwrap().capacity()
+        self.inner.state.lock().unwrap().stack.capacity()
     }
 
     pub fn contains(&self, item: &T) -> bool
     where
         T: PartialEq,
     {
-        self.stack.lock().unwrap().contains(item)
+        self.inner.state.lock().unwrap().stack.contains(item)
     }
 
     pub fn reverse(&self) -> VecDeque<T>
     where
         T: Clone,
     {
-        let mut stack = self.stack.lock().unwrap().clone();
+        let mut stack = self.inner.state.lock().unwrap().stack.clone();
         stack.make_contiguous().reverse();
-        
+
         stack
     }
 }
@@ -94,8 +242,7 @@ impl<T> Default for BlockingStack<T> {
 impl<T> Clone for BlockingStack<T> {
     fn clone(&self) -> Self {
         Self {
-            stack: Arc::clone(&self.stack),
-            cvar: Condvar::new(),
+            inner: Arc::clone(&self.inner),
         }
     }
 }
@@ -105,19 +252,46 @@ where
     T: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let stack = self.stack.lock().unwrap();
+        let state = self.inner.state.lock().unwrap();
         f.debug_struct("BlockingStack")
-            .field("stack", &*stack)
+            .field("stack", &state.stack)
+            .field("bound", &self.inner.bound)
+            .field("closed", &state.closed)
             .finish()
     }
 }
 
 impl<T> From<Vec<T>> for BlockingStack<T> {
     fn from(v: Vec<T>) -> Self {
-        Self {
-            stack: Arc::new(Mutex::new(VecDeque::from(v))),
-            cvar: Condvar::new(),
-        }
+        Self::from_parts(VecDeque::from(v), None)
+    }
+}
+
+impl<T> ConcurrentStack<T> for BlockingStack<T> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.try_push(item)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> BlockingConcurrentStack<T> for BlockingStack<T> {
+    fn push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push(item)
+    }
+
+    fn pop(&self) -> Result<T, PopError> {
+        self.pop()
     }
 }
 
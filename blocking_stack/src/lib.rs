@@ -0,0 +1,4 @@
+mod blocking_stack;
+
+pub use blocking_stack::BlockingStack;
+pub use concurrency_core::{PopError, PopTimeoutError, PushError, PushTimeoutError};
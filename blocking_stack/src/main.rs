@@ -1,7 +1,9 @@
-mod blocking_stack;
-
-use blocking_stack::BlockingStack;
-use std::{sync::Arc, thread, time::Duration};
+use blocking_stack::{BlockingStack, PopError, PopTimeoutError, PushError, PushTimeoutError};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 fn main() {
     println!("Testing Single-threaded BlockingStack...");
@@ -9,13 +11,13 @@ fn main() {
     let stack = BlockingStack::new();
     println!("Created new stack: {:?}", stack);
 
-    stack.push(1);
-    stack.push(2);
-    stack.push(3);
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    stack.push(3).unwrap();
     println!("After pushing 1, 2, 3: {:?}", stack);
 
-    assert_eq!(stack.pop(), 3);
-    assert_eq!(stack.pop(), 2);
+    assert_eq!(stack.pop(), Ok(3));
+    assert_eq!(stack.pop(), Ok(2));
     println!("After popping twice: {:?}", stack);
 
     assert_eq!(stack.try_pop(), Some(1));
@@ -30,7 +32,7 @@ fn main() {
         stack.len()
     );
 
-    stack.push(4);
+    stack.push(4).unwrap();
     assert_eq!(stack.peek(), Some(4));
     println!("Peeked: {:?}", stack.peek());
 
@@ -38,17 +40,17 @@ fn main() {
     assert!(stack.is_empty());
     println!("Cleared stack: {:?}", stack);
 
-    stack.push(5);
-    stack.push(6);
-    stack.push(7);
+    stack.push(5).unwrap();
+    stack.push(6).unwrap();
+    stack.push(7).unwrap();
     let drained = stack.drain();
-    assert_eq!(drained, vec![7, 6, 5]);
+    assert_eq!(drained, vec![5, 6, 7]);
     assert!(stack.is_empty());
     println!("Drained: {:?}, Stack now: {:?}", drained, stack);
     println!("Capacity: {}", stack.capacity());
 
-    stack.push(8);
-    stack.push(9);
+    stack.push(8).unwrap();
+    stack.push(9).unwrap();
     assert!(stack.contains(&8));
     assert!(!stack.contains(&10));
     println!(
@@ -58,7 +60,7 @@ fn main() {
     );
 
     let reversed = stack.reverse();
-    assert_eq!(reversed, vec![8, 9]);
+    assert_eq!(reversed, vec![9, 8]);
     println!("Reversed: {:?}", reversed);
 
     let cloned_stack = stack.clone();
@@ -74,15 +76,15 @@ fn main() {
 
     let producer = thread::spawn(move || {
         for i in 0..5 {
-            stack_clone.push(i);
+            stack_clone.push(i).unwrap();
             println!("Produced: {}", i);
             thread::sleep(Duration::from_millis(100));
         }
+        stack_clone.close();
     });
 
     let consumer = thread::spawn(move || {
-        for _ in 0..5 {
-            let item = stack.pop();
+        while let Ok(item) = stack.pop() {
             println!("Consumed: {}", item);
             thread::sleep(Duration::from_millis(150));
         }
@@ -93,18 +95,85 @@ fn main() {
 
     println!("Multi-threaded test completed.");
 
+    println!("Testing bounded BlockingStack");
+    let stack = Arc::new(BlockingStack::bounded(2));
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    assert_eq!(stack.try_push(3), Err(3));
+    println!("Full stack rejected try_push: {:?}", stack);
+
+    let stack_clone = Arc::clone(&stack);
+    let blocked_producer = thread::spawn(move || {
+        stack_clone.push(3).unwrap();
+        println!("Producer unblocked");
+    });
+
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.pop(), Ok(2));
+    blocked_producer.join().unwrap();
+    assert_eq!(stack.try_pop(), Some(3));
+    assert_eq!(stack.try_pop(), Some(1));
+    println!("Bounded test completed.");
+
+    println!("Testing timed waits");
+    let stack = BlockingStack::bounded(1);
+    assert_eq!(
+        stack.pop_timeout(Duration::from_millis(50)),
+        Err(PopTimeoutError::Timeout)
+    );
+    stack.push(1).unwrap();
+    assert_eq!(
+        stack.push_timeout(2, Duration::from_millis(50)),
+        Err(PushTimeoutError::Timeout(2))
+    );
+    assert_eq!(stack.pop_timeout(Duration::from_millis(50)), Ok(1));
+    assert_eq!(stack.push_timeout(2, Duration::from_millis(50)), Ok(()));
+    assert_eq!(
+        stack.pop_deadline(Instant::now() + Duration::from_millis(50)),
+        Ok(2)
+    );
+    println!("Timed waits completed.");
+
+    println!("Testing close");
+    let stack = Arc::new(BlockingStack::<i32>::new());
+    let stack_clone = Arc::clone(&stack);
+    let blocked_consumer = thread::spawn(move || stack_clone.pop());
+
+    thread::sleep(Duration::from_millis(100));
+    stack.close();
+    assert_eq!(blocked_consumer.join().unwrap(), Err(PopError::Closed));
+
+    let stack = BlockingStack::new();
+    stack.push(1).unwrap();
+    stack.push(2).unwrap();
+    stack.close();
+    assert!(stack.is_closed());
+    assert_eq!(stack.push(3), Err(PushError::Closed(3)));
+    assert_eq!(stack.try_push(3), Err(3));
+    let mut remaining = Vec::new();
+    while let Ok(item) = stack.pop() {
+        remaining.push(item);
+    }
+    assert_eq!(remaining, vec![2, 1]);
+    assert_eq!(
+        stack.pop_timeout(Duration::from_millis(50)),
+        Err(PopTimeoutError::Closed)
+    );
+    println!("Close test completed.");
+
     let stack = Arc::new(BlockingStack::new());
     let stack_clone = Arc::clone(&stack);
 
     let blocking_thread = thread::spawn(move || {
         println!("Waiting for item...");
-        let item = stack_clone.pop();
+        let item = stack_clone.pop().unwrap();
         println!("Received item: {}", item);
     });
 
     thread::sleep(Duration::from_secs(1));
     println!("Pushing item to unblock thread");
-    stack.push(42);
+    stack.push(42).unwrap();
 
     blocking_thread.join().unwrap();
 }
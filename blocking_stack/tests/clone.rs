@@ -0,0 +1,49 @@
+use blocking_stack::BlockingStack;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// A waiter parked on one clone must be woken by an operation on another. The
+// result comes back over a channel so a regression fails instead of hanging.
+const WAKEUP: Duration = Duration::from_secs(5);
+
+#[test]
+fn push_through_clone_wakes_blocked_pop() {
+    let stack = BlockingStack::new();
+    let consumer_stack = stack.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(consumer_stack.pop()).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    stack.clone().push(7).unwrap();
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Ok(7)));
+}
+
+#[test]
+fn pop_through_clone_wakes_blocked_push() {
+    let stack = BlockingStack::bounded(1);
+    stack.push(1).unwrap();
+    let producer_stack = stack.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(producer_stack.push(2)).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(stack.clone().pop(), Ok(1));
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(Ok(())));
+    assert_eq!(stack.pop(), Ok(2));
+}
+
+#[test]
+fn close_through_clone_wakes_blocked_pop() {
+    let stack = BlockingStack::<i32>::new();
+    let consumer_stack = stack.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || tx.send(consumer_stack.pop().is_err()).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    stack.clone().close();
+
+    assert_eq!(rx.recv_timeout(WAKEUP), Ok(true));
+}
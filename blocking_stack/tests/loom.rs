@@ -0,0 +1,74 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p blocking_stack --release --test loom`.
+use blocking_stack::BlockingStack;
+use loom::thread;
+
+#[test]
+fn pop_is_woken_by_push() {
+    loom::model(|| {
+        let stack = BlockingStack::new();
+        let consumer = {
+            let stack = stack.clone();
+            thread::spawn(move || stack.pop())
+        };
+
+        stack.push(1).unwrap();
+        assert_eq!(consumer.join().unwrap(), Ok(1));
+    });
+}
+
+#[test]
+fn push_into_full_stack_is_woken_by_pop() {
+    loom::model(|| {
+        let stack = BlockingStack::bounded(1);
+        stack.push(1).unwrap();
+        let producer = {
+            let stack = stack.clone();
+            thread::spawn(move || stack.push(2))
+        };
+
+        assert_eq!(stack.pop(), Ok(1));
+        assert_eq!(producer.join().unwrap(), Ok(()));
+        assert_eq!(stack.try_pop(), Some(2));
+    });
+}
+
+#[test]
+fn racing_pushes_keep_each_producer_in_order() {
+    loom::model(|| {
+        let stack = BlockingStack::new();
+        let producer = {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                stack.push(1).unwrap();
+                stack.push(2).unwrap();
+            })
+        };
+
+        stack.push(3).unwrap();
+        producer.join().unwrap();
+        let mut values = Vec::new();
+        while let Some(value) = stack.try_pop() {
+            values.push(value);
+        }
+        let ones = values.iter().position(|&v| v == 1).unwrap();
+        let twos = values.iter().position(|&v| v == 2).unwrap();
+        assert!(twos < ones);
+        assert_eq!(values.len(), 3);
+    });
+}
+
+#[test]
+fn close_wakes_blocked_pop() {
+    loom::model(|| {
+        let stack = BlockingStack::<i32>::new();
+        let consumer = {
+            let stack = stack.clone();
+            thread::spawn(move || stack.pop())
+        };
+
+        stack.close();
+        assert!(consumer.join().unwrap().is_err());
+    });
+}
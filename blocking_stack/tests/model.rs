@@ -0,0 +1,78 @@
+use blocking_stack::BlockingStack;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+// Runs random single-threaded operation sequences against the stack and a
+// `Vec` whose end is the top, checking every result. proptest shrinks a
+// failing sequence down to the shortest one that still fails. Values are
+// drawn from a small range so `contains` hits as often as it misses.
+#[derive(Debug, Clone)]
+enum Op {
+    TryPush(u8),
+    TryPop,
+    Peek,
+    Contains(u8),
+    Reverse,
+    Drain,
+    Clear,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (0..8u8).prop_map(Op::TryPush),
+        3 => Just(Op::TryPop),
+        1 => Just(Op::Peek),
+        2 => (0..8u8).prop_map(Op::Contains),
+        1 => Just(Op::Reverse),
+        1 => Just(Op::Drain),
+        1 => Just(Op::Clear),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn blocking_stack_matches_vec(
+        bound in proptest::option::of(1..8usize),
+        ops in vec(op(), 0..64),
+    ) {
+        let stack = match bound {
+            Some(bound) => BlockingStack::bounded(bound),
+            None => BlockingStack::new(),
+        };
+        let mut model = Vec::new();
+        for op in ops {
+            match op {
+                Op::TryPush(value) => {
+                    let fits = bound.is_none_or(|bound| model.len() < bound);
+                    let expected = if fits {
+                        model.push(value);
+                        Ok(())
+                    } else {
+                        Err(value)
+                    };
+                    prop_assert_eq!(stack.try_push(value), expected);
+                }
+                Op::TryPop => prop_assert_eq!(stack.try_pop(), model.pop()),
+                Op::Peek => prop_assert_eq!(stack.peek(), model.last().copied()),
+                Op::Contains(value) => {
+                    prop_assert_eq!(stack.contains(&value), model.contains(&value));
+                }
+                // Top first.
+                Op::Reverse => {
+                    let expected: Vec<_> = model.iter().rev().copied().collect();
+                    prop_assert_eq!(Vec::from(stack.reverse()), expected);
+                }
+                // Bottom first.
+                Op::Drain => {
+                    prop_assert_eq!(stack.drain(), std::mem::take(&mut model));
+                }
+                Op::Clear => {
+                    stack.clear();
+                    model.clear();
+                }
+            }
+            prop_assert_eq!(stack.len(), model.len());
+            prop_assert_eq!(stack.is_empty(), model.is_empty());
+        }
+    }
+}
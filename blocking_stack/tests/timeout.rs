@@ -0,0 +1,51 @@
+use blocking_stack::{BlockingStack, PopTimeoutError, PushTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+// Upper bound on how late a timed-out call may return, generous enough for a
+// loaded CI machine.
+const SLACK: Duration = Duration::from_secs(5);
+
+#[test]
+fn pop_timeout_on_empty_stack_times_out_after_the_duration() {
+    let stack = BlockingStack::<u8>::new();
+    let start = Instant::now();
+
+    assert_eq!(stack.pop_timeout(TIMEOUT), Err(PopTimeoutError::Timeout));
+    let elapsed = start.elapsed();
+    assert!(elapsed >= TIMEOUT, "returned after {elapsed:?}");
+    assert!(elapsed < TIMEOUT + SLACK, "returned after {elapsed:?}");
+}
+
+#[test]
+fn push_timeout_into_full_stack_times_out_with_the_item() {
+    let stack = BlockingStack::bounded(1);
+    stack.push(1).unwrap();
+    let start = Instant::now();
+
+    assert_eq!(
+        stack.push_timeout(2, TIMEOUT),
+        Err(PushTimeoutError::Timeout(2))
+    );
+    assert!(start.elapsed() >= TIMEOUT);
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+fn push_timeout_into_full_stack_succeeds_once_a_pop_frees_room() {
+    let stack = BlockingStack::bounded(1);
+    stack.push(1).unwrap();
+    let consumer = {
+        let stack = stack.clone();
+        thread::spawn(move || {
+            thread::sleep(TIMEOUT);
+            stack.pop()
+        })
+    };
+
+    assert_eq!(stack.push_timeout(2, SLACK), Ok(()));
+    assert_eq!(consumer.join().unwrap(), Ok(1));
+    assert_eq!(stack.pop_timeout(TIMEOUT), Ok(2));
+}
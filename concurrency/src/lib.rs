@@ -0,0 +1,34 @@
+//! Umbrella crate re-exporting every structure in the workspace.
+//!
+//! Each structure sits behind a cargo feature named after its crate, all of
+//! which are enabled by default. Depend on this crate with
+//! `default-features = false` and pick the features you need to compile only
+//! those structures.
+//!
+//! The shared traits, reclamation backends and backoff strategies from
+//! `concurrency_core` are always re-exported, so code can be written against
+//! them regardless of which features are enabled.
+
+pub use concurrency_core::{
+    Backoff, Blocking, BlockingConcurrentQueue, BlockingConcurrentStack, ConcurrentQueue,
+    ConcurrentStack, Epoch, Exponential, Hazard, LenCounter, LenMode, NoBackoff, PopError,
+    PopTimeoutError, PushError, PushTimeoutError, Reclaim, Spin, SpinThenYield, WakerSet,
+};
+
+#[cfg(feature = "blocking_queue")]
+pub use blocking_queue::{self, BlockingQueue};
+
+#[cfg(feature = "blocking_stack")]
+pub use blocking_stack::{self, BlockingStack};
+
+#[cfg(feature = "concurrent_pool")]
+pub use concurrent_pool::{self, ConcurrentPool, PoolError};
+
+#[cfg(feature = "nonblocking_queue")]
+pub use nonblocking_queue::{self, spsc, AsyncQueue, Consumer, LockFreeQueue, Producer};
+
+#[cfg(feature = "nonblocking_stack")]
+pub use nonblocking_stack::{self, LockFreeStack};
+
+#[cfg(feature = "ring_queue")]
+pub use ring_queue::{self, RingQueue};
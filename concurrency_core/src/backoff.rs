@@ -0,0 +1,124 @@
+use crate::{random, sync};
+
+/// How a lock-free structure waits before retrying an operation that lost a
+/// race with another thread.
+///
+/// `step` counts the attempts the current operation has lost so far,
+/// starting at `0`. Which strategy pays off depends on how many threads
+/// contend for how many cores, so every lock-free structure takes one as a
+/// type parameter, e.g. `LockFreeQueue::new().with_backoff(Spin::new(4))`.
+pub trait Backoff {
+    fn wait(&self, step: u32);
+}
+
+/// Retries straight away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoBackoff;
+
+impl Backoff for NoBackoff {
+    fn wait(&self, _step: u32) {}
+}
+
+/// Busy-waits with `spin_loop`, doubling the spins after every lost attempt
+/// up to `2^limit`. Never gives up the core, so it only suits threads that
+/// don't outnumber the cores.
+#[derive(Debug, Clone, Copy)]
+pub struct Spin {
+    limit: u32,
+}
+
+impl Spin {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit: limit.min(MAX_SHIFT),
+        }
+    }
+}
+
+impl Default for Spin {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+impl Backoff for Spin {
+    fn wait(&self, step: u32) {
+        spin(1 << step.min(self.limit));
+    }
+}
+
+/// Spins like [`Spin`] for the first `spin_limit` lost attempts, then
+/// yields to the scheduler on every attempt after that. The default, since
+/// it stays cheap under light contention and lets a preempted thread that
+/// the others are waiting on get back on a core.
+#[derive(Debug, Clone, Copy)]
+pub struct SpinThenYield {
+    spin_limit: u32,
+}
+
+impl SpinThenYield {
+    pub fn new(spin_limit: u32) -> Self {
+        Self {
+            spin_limit: spin_limit.min(MAX_SHIFT),
+        }
+    }
+}
+
+impl Default for SpinThenYield {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+impl Backoff for SpinThenYield {
+    fn wait(&self, step: u32) {
+        if step < self.spin_limit {
+            spin(1 << step);
+        } else {
+            sync::yield_now();
+        }
+    }
+}
+
+/// Spins for a random number of iterations below a ceiling that doubles
+/// after every lost attempt and stops at `cap`.
+///
+/// The randomness keeps threads that lost the same race from retrying in
+/// lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct Exponential {
+    cap: u32,
+}
+
+impl Exponential {
+    pub fn new(cap: u32) -> Self {
+        Self { cap: cap.max(1) }
+    }
+}
+
+impl Default for Exponential {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl Backoff for Exponential {
+    fn wait(&self, step: u32) {
+        let ceiling = 1u32.checked_shl(step).unwrap_or(u32::MAX).min(self.cap);
+        spin(random::next_u32() % ceiling + 1);
+    }
+}
+
+const MAX_SHIFT: u32 = 20;
+
+// Under loom every spin is a scheduling point, so one is all a model needs
+// to let the other threads run.
+fn spin(count: u32) {
+    if cfg!(loom) {
+        sync::yield_now();
+        return;
+    }
+    for _ in 0..count {
+        sync::hint::spin_loop();
+    }
+}
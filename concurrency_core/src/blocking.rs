@@ -0,0 +1,278 @@
+use crate::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use crate::sync::{Condvar, Mutex};
+use crate::{
+    BlockingConcurrentQueue, BlockingConcurrentStack, ConcurrentQueue, ConcurrentStack, PopError,
+    PushError,
+};
+use std::fmt;
+
+/// Adds blocking `push` and `pop` to a non-blocking structure.
+///
+/// Works with anything implementing [`ConcurrentQueue`] or
+/// [`ConcurrentStack`], e.g. `Blocking::new(LockFreeQueue::new())`. Pushes
+/// and pops that succeed straight away cost a fence and a load on top of the
+/// wrapped operation; the mutex is only touched when a thread actually has
+/// to sleep or be woken.
+///
+/// Go through the wrapper for every push and pop. Items pushed through
+/// [`Blocking::get_ref`] don't wake anyone waiting in `pop`.
+pub struct Blocking<Q> {
+    inner: Q,
+    not_empty: EventCount,
+    not_full: EventCount,
+    closed: AtomicBool,
+}
+
+impl<Q> Blocking<Q> {
+    pub fn new(inner: Q) -> Self {
+        Self {
+            inner,
+            not_empty: EventCount::new(),
+            not_full: EventCount::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn get_ref(&self) -> &Q {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Q {
+        self.inner
+    }
+
+    /// Closes the structure and wakes every blocked `push` and `pop`.
+    ///
+    /// Pushes fail from then on, while pops keep draining whatever is left.
+    /// A push that was already past its check when `close` ran may still
+    /// land, so don't rely on close to fence off concurrent producers.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn try_push_with<T>(
+        &self,
+        item: T,
+        try_push: impl Fn(&Q, T) -> Result<(), T>,
+    ) -> Result<(), T> {
+        if self.is_closed() {
+            return Err(item);
+        }
+        try_push(&self.inner, item)?;
+        self.not_empty.notify_one();
+
+        Ok(())
+    }
+
+    fn try_pop_with<T>(&self, try_pop: impl Fn(&Q) -> Option<T>) -> Option<T> {
+        let item = try_pop(&self.inner)?;
+        self.not_full.notify_one();
+
+        Some(item)
+    }
+
+    fn push_with<T>(
+        &self,
+        mut item: T,
+        try_push: impl Fn(&Q, T) -> Result<(), T>,
+    ) -> Result<(), PushError<T>> {
+        loop {
+            if self.is_closed() {
+                return Err(PushError::Closed(item));
+            }
+            item = match self.try_push_with(item, &try_push) {
+                Ok(()) => return Ok(()),
+                Err(item) => item,
+            };
+
+            // Register before trying again so a pop landing in between
+            // either makes room for the second try or changes the key.
+            let key = self.not_full.prepare_wait();
+            if self.is_closed() {
+                self.not_full.cancel_wait();
+                continue;
+            }
+            item = match self.try_push_with(item, &try_push) {
+                Ok(()) => {
+                    self.not_full.cancel_wait();
+                    return Ok(());
+                }
+                Err(item) => item,
+            };
+            self.not_full.wait(key);
+        }
+    }
+
+    fn pop_with<T>(&self, try_pop: impl Fn(&Q) -> Option<T>) -> Result<T, PopError> {
+        loop {
+            // Closing is final, so if it was seen before an empty pop there
+            // is nothing left to wait for.
+            let closed = self.is_closed();
+            if let Some(item) = self.try_pop_with(&try_pop) {
+                return Ok(item);
+            }
+            if closed {
+                return Err(PopError::Closed);
+            }
+
+            let key = self.not_empty.prepare_wait();
+            if self.is_closed() {
+                self.not_empty.cancel_wait();
+                continue;
+            }
+            if let Some(item) = self.try_pop_with(&try_pop) {
+                self.not_empty.cancel_wait();
+                return Ok(item);
+            }
+            self.not_empty.wait(key);
+        }
+    }
+}
+
+impl<Q: Default> Default for Blocking<Q> {
+    fn default() -> Self {
+        Self::new(Q::default())
+    }
+}
+
+impl<Q: fmt::Debug> fmt::Debug for Blocking<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blocking")
+            .field("inner", &self.inner)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T, Q: ConcurrentQueue<T>> ConcurrentQueue<T> for Blocking<Q> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.try_push_with(item, Q::try_push)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop_with(Q::try_pop)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, Q: ConcurrentQueue<T>> BlockingConcurrentQueue<T> for Blocking<Q> {
+    fn push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push_with(item, Q::try_push)
+    }
+
+    fn pop(&self) -> Result<T, PopError> {
+        self.pop_with(Q::try_pop)
+    }
+}
+
+impl<T, Q: ConcurrentStack<T>> ConcurrentStack<T> for Blocking<Q> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.try_push_with(item, Q::try_push)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop_with(Q::try_pop)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, Q: ConcurrentStack<T>> BlockingConcurrentStack<T> for Blocking<Q> {
+    fn push(&self, item: T) -> Result<(), PushError<T>> {
+        self.push_with(item, Q::try_push)
+    }
+
+    fn pop(&self) -> Result<T, PopError> {
+        self.pop_with(Q::try_pop)
+    }
+}
+
+// A waiter bumps `waiters`, reads `epoch` as its key, re-checks its condition
+// and only then sleeps until the epoch moves past the key. A notifier makes
+// its change visible, then bumps the epoch if anyone is registered. The
+// SeqCst fences on both sides guarantee that either the waiter's re-check
+// sees the change or the notifier sees the waiter, so no wakeup is lost, and
+// notifiers with no registered waiters never touch the mutex.
+struct EventCount {
+    epoch: AtomicUsize,
+    waiters: AtomicUsize,
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+
+impl EventCount {
+    fn new() -> Self {
+        Self {
+            epoch: AtomicUsize::new(0),
+            waiters: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cond: Condvar::new(),
+        }
+    }
+
+    // Must be followed by exactly one `wait` or `cancel_wait`.
+    fn prepare_wait(&self) -> usize {
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let key = self.epoch.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+
+        key
+    }
+
+    fn cancel_wait(&self) {
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn wait(&self, key: usize) {
+        let mut guard = self.lock.lock().unwrap();
+        while self.epoch.load(Ordering::Acquire) == key {
+            guard = self.cond.wait(guard).unwrap();
+        }
+        drop(guard);
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn notify_one(&self) {
+        if self.bump() {
+            self.cond.notify_one();
+        }
+    }
+
+    fn notify_all(&self) {
+        if self.bump() {
+            self.cond.notify_all();
+        }
+    }
+
+    fn bump(&self) -> bool {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        self.epoch.fetch_add(1, Ordering::Release);
+        // A waiter checks the epoch under the lock, so taking it here means
+        // any waiter that saw the old epoch is already asleep on `cond`.
+        drop(self.lock.lock().unwrap());
+
+        true
+    }
+}
@@ -0,0 +1,111 @@
+use crate::sync::atomic::{AtomicIsize, Ordering};
+use std::fmt;
+use std::sync::atomic::AtomicUsize;
+use std::sync::OnceLock;
+
+/// How [`LenCounter`] keeps count, and so what `len` guarantees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LenMode {
+    /// Every thread counts on one of several stripes, so pushes and pops on
+    /// different cores don't fight over a cache line. `len` adds the
+    /// stripes up one after another, so while pushes and pops are running
+    /// the sum can be off in either direction by as many operations as ran
+    /// during the call. It is exact once they have all returned, and never
+    /// negative.
+    #[default]
+    Approximate,
+    /// One shared counter, read with a single load. A push counts itself
+    /// before it links its item and a pop after it unlinks one, so while
+    /// they are running `len` can only overcount, by at most the pushes in
+    /// flight. It is exact once they have all returned. Every push and pop
+    /// writes the same cache line, which is fine for tests but scales
+    /// poorly.
+    Exact,
+}
+
+/// An item count for a lock-free structure.
+pub struct LenCounter {
+    mode: LenMode,
+    stripes: Box<[Stripe]>,
+}
+
+#[repr(align(64))]
+struct Stripe(AtomicIsize);
+
+impl LenCounter {
+    pub fn new(mode: LenMode) -> Self {
+        let stripes = match mode {
+            LenMode::Approximate => stripe_count(),
+            LenMode::Exact => 1,
+        };
+
+        Self {
+            mode,
+            stripes: (0..stripes).map(|_| Stripe(AtomicIsize::new(0))).collect(),
+        }
+    }
+
+    pub fn mode(&self) -> LenMode {
+        self.mode
+    }
+
+    pub fn add(&self, count: usize) {
+        self.stripe().fetch_add(count as isize, Ordering::Relaxed);
+    }
+
+    pub fn sub(&self, count: usize) {
+        self.stripe().fetch_sub(count as isize, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> usize {
+        let sum: isize = self
+            .stripes
+            .iter()
+            .map(|stripe| stripe.0.load(Ordering::Relaxed))
+            .sum();
+
+        sum.max(0) as usize
+    }
+
+    fn stripe(&self) -> &AtomicIsize {
+        &self.stripes[thread_index() & (self.stripes.len() - 1)].0
+    }
+}
+
+impl Default for LenCounter {
+    fn default() -> Self {
+        Self::new(LenMode::default())
+    }
+}
+
+impl fmt::Debug for LenCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LenCounter")
+            .field("mode", &self.mode)
+            .field("len", &self.get())
+            .finish()
+    }
+}
+
+// A power of two at least as large as the core count, so threads that run
+// at the same time mostly land on different stripes.
+fn stripe_count() -> usize {
+    static STRIPES: OnceLock<usize> = OnceLock::new();
+
+    *STRIPES.get_or_init(|| {
+        std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .next_power_of_two()
+            .min(64)
+    })
+}
+
+// Threads take stripes round-robin in the order they first count anything.
+fn thread_index() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    INDEX.with(|index| *index)
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::fmt;
+
+/// Returned by `pop` on a blocking structure that has been closed and drained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopError {
+    /// The structure was closed and every remaining item has been popped.
+    Closed,
+}
+
+impl fmt::Display for PopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopError::Closed => f.write_str("popping from a closed and empty structure"),
+        }
+    }
+}
+
+impl Error for PopError {}
+
+/// Returned by `push` on a blocking structure that has been closed. Carries
+/// the item that couldn't be pushed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// The structure was closed before the item could be pushed.
+    Closed(T),
+}
+
+impl<T> PushError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            PushError::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Closed(_) => f.write_str("pushing into a closed structure"),
+        }
+    }
+}
+
+impl<T> Error for PushError<T> {}
+
+/// Returned by the timed `pop` variants of the blocking structures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopTimeoutError {
+    /// Nothing arrived before the deadline.
+    Timeout,
+    /// The structure was closed and every remaining item has been popped.
+    Closed,
+}
+
+impl fmt::Display for PopTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopTimeoutError::Timeout => f.write_str("timed out waiting for an item"),
+            PopTimeoutError::Closed => f.write_str("popping from a closed and empty structure"),
+        }
+    }
+}
+
+impl Error for PopTimeoutError {}
+
+impl From<PopError> for PopTimeoutError {
+    fn from(err: PopError) -> Self {
+        match err {
+            PopError::Closed => PopTimeoutError::Closed,
+        }
+    }
+}
+
+/// Returned by the timed `push` variants of the blocking structures. Carries
+/// the item that couldn't be pushed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PushTimeoutError<T> {
+    /// No room was made before the deadline.
+    Timeout(T),
+    /// The structure was closed before the item could be pushed.
+    Closed(T),
+}
+
+impl<T> PushTimeoutError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            PushTimeoutError::Timeout(item) | PushTimeoutError::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushTimeoutError::Timeout(_) => f.write_str("Timeout(..)"),
+            PushTimeoutError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushTimeoutError::Timeout(_) => f.write_str("timed out waiting for room"),
+            PushTimeoutError::Closed(_) => f.write_str("pushing into a closed structure"),
+        }
+    }
+}
+
+impl<T> Error for PushTimeoutError<T> {}
+
+impl<T> From<PushError<T>> for PushTimeoutError<T> {
+    fn from(err: PushError<T>) -> Self {
+        match err {
+            PushError::Closed(item) => PushTimeoutError::Closed(item),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::reclaim::Reclaim;
+use crate::sync;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// The domain is a `static` and records are cached per OS thread, neither of
+// which loom can model, so the internals stay on `std` even under
+// `cfg(loom)`. Only the pointers being protected come from `crate::sync`.
+
+const SLOTS: usize = 3;
+
+// A thread scans once its retire list reaches this many nodes, or twice the
+// number of hazard slots in the domain if that's larger. Either way at most
+// `threshold + slots` retired nodes per thread can be left unfreed.
+const SCAN_THRESHOLD: usize = 64;
+
+/// Hazard-pointer reclamation.
+///
+/// Every guard owns a record of hazard slots. Retired nodes are freed by a
+/// scan as soon as no slot points at them, so the amount of garbage stays
+/// bounded even if a thread stalls while holding a guard.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hazard;
+
+impl Hazard {
+    /// Scans the calling thread's retire list now instead of waiting for the
+    /// threshold, freeing every node that isn't protected.
+    pub fn flush() {
+        let _ = LOCAL.try_with(|local| local.scan());
+    }
+}
+
+impl Reclaim for Hazard {
+    type Guard = HazardGuard;
+
+    fn pin() -> Self::Guard {
+        HazardGuard {
+            record: acquire_record(),
+            _not_send: PhantomData,
+        }
+    }
+
+    fn protect<T>(guard: &Self::Guard, slot: usize, src: &sync::atomic::AtomicPtr<T>) -> *mut T {
+        guard.protect(slot, src)
+    }
+
+    unsafe fn retire<T>(_guard: &Self::Guard, ptr: *mut T) {
+        let mut retired = Some(Retired {
+            ptr: ptr.cast(),
+            drop: drop_box::<T>,
+        });
+
+        let _ = LOCAL.try_with(|local| local.retire(retired.take().unwrap()));
+        if let Some(retired) = retired {
+            DOMAIN.orphans.lock().unwrap().push(retired);
+        }
+    }
+}
+
+pub struct HazardGuard {
+    record: &'static Record,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl HazardGuard {
+    fn protect<T>(&self, slot: usize, src: &sync::atomic::AtomicPtr<T>) -> *mut T {
+        let hazard = &self.record.slots[slot];
+        let mut ptr = src.load(Ordering::Acquire);
+        loop {
+            hazard.store(ptr.cast(), Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+
+            let current = src.load(Ordering::Acquire);
+            if current == ptr {
+                return ptr;
+            }
+
+            ptr = current;
+        }
+    }
+}
+
+impl Drop for HazardGuard {
+    fn drop(&mut self) {
+        for slot in &self.record.slots {
+            slot.store(ptr::null_mut(), Ordering::Release);
+        }
+
+        release_record(self.record);
+    }
+}
+
+struct Record {
+    active: AtomicBool,
+    slots: [AtomicPtr<u8>; SLOTS],
+    next: *const Record,
+}
+
+struct Retired {
+    ptr: *mut u8,
+    drop: unsafe fn(*mut u8),
+}
+
+// Retired nodes are owned by the reclaimer alone, and `Reclaim::retire`
+// requires that they can be dropped on any thread.
+unsafe impl Send for Retired {}
+
+unsafe fn drop_box<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr.cast::<T>()));
+}
+
+// Records are leaked on purpose: a scan may be reading any of them at any
+// time, so they're reused through `active` instead of being freed.
+struct Domain {
+    records: AtomicPtr<Record>,
+    record_count: AtomicUsize,
+    orphans: Mutex<Vec<Retired>>,
+}
+
+static DOMAIN: Domain = Domain {
+    records: AtomicPtr::new(ptr::null_mut()),
+    record_count: AtomicUsize::new(0),
+    orphans: Mutex::new(Vec::new()),
+};
+
+struct Local {
+    retired: RefCell<Vec<Retired>>,
+    cached: Cell<*const Record>,
+}
+
+thread_local! {
+    static LOCAL: Local = const {
+        Local {
+            retired: RefCell::new(Vec::new()),
+            cached: Cell::new(ptr::null()),
+        }
+    };
+}
+
+impl Local {
+    fn retire(&self, retired: Retired) {
+        let len = {
+            let mut list = self.retired.borrow_mut();
+            list.push(retired);
+            list.len()
+        };
+
+        let slots = SLOTS * DOMAIN.record_count.load(Ordering::Relaxed);
+        if len >= SCAN_THRESHOLD.max(2 * slots) {
+            self.scan();
+        }
+    }
+
+    fn scan(&self) {
+        // Take the list out first: dropping a node can retire more nodes.
+        let mut list = mem::take(&mut *self.retired.borrow_mut());
+        scan(&mut list);
+        self.retired.borrow_mut().append(&mut list);
+    }
+}
+
+impl Drop for Local {
+    fn drop(&mut self) {
+        let cached = self.cached.replace(ptr::null());
+        if !cached.is_null() {
+            unsafe { (*cached).active.store(false, Ordering::Release) };
+        }
+
+        let mut list = mem::take(self.retired.get_mut());
+        scan(&mut list);
+        if !list.is_empty() {
+            DOMAIN.orphans.lock().unwrap().append(&mut list);
+        }
+    }
+}
+
+fn scan(list: &mut Vec<Retired>) {
+    if let Ok(mut orphans) = DOMAIN.orphans.try_lock() {
+        list.append(&mut orphans);
+    }
+
+    fence(Ordering::SeqCst);
+
+    let mut hazards = Vec::new();
+    let mut record = DOMAIN.records.load(Ordering::Acquire).cast_const();
+    while let Some(rec) = unsafe { record.as_ref() } {
+        for slot in &rec.slots {
+            let ptr = slot.load(Ordering::Acquire);
+            if !ptr.is_null() {
+                hazards.push(ptr);
+            }
+        }
+
+        record = rec.next;
+    }
+
+    hazards.sort_unstable();
+
+    let (keep, free): (Vec<_>, Vec<_>) = mem::take(list)
+        .into_iter()
+        .partition(|retired| hazards.binary_search(&retired.ptr).is_ok());
+
+    *list = keep;
+    for retired in free {
+        unsafe { (retired.drop)(retired.ptr) };
+    }
+}
+
+fn acquire_record() -> &'static Record {
+    let cached = LOCAL
+        .try_with(|local| local.cached.replace(ptr::null()))
+        .unwrap_or(ptr::null());
+    if let Some(record) = unsafe { cached.as_ref() } {
+        return record;
+    }
+
+    let mut record = DOMAIN.records.load(Ordering::Acquire).cast_const();
+    while let Some(rec) = unsafe { record.as_ref() } {
+        if rec
+            .active
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            return rec;
+        }
+
+        record = rec.next;
+    }
+
+    let new = Box::into_raw(Box::new(Record {
+        active: AtomicBool::new(true),
+        slots: Default::default(),
+        next: ptr::null(),
+    }));
+
+    let mut head = DOMAIN.records.load(Ordering::Acquire);
+    loop {
+        unsafe { (*new).next = head };
+        match DOMAIN
+            .records
+            .compare_exchange(head, new, Ordering::Release, Ordering::Acquire)
+        {
+            Ok(_) => break,
+            Err(current) => head = current,
+        }
+    }
+
+    DOMAIN.record_count.fetch_add(1, Ordering::Relaxed);
+    unsafe { &*new }
+}
+
+fn release_record(record: &'static Record) {
+    let cached = LOCAL.try_with(|local| {
+        if local.cached.get().is_null() {
+            local.cached.set(record);
+            true
+        } else {
+            false
+        }
+    });
+
+    if cached != Ok(true) {
+        record.active.store(false, Ordering::Release);
+    }
+}
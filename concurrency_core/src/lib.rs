@@ -0,0 +1,21 @@
+mod backoff;
+mod blocking;
+mod counter;
+mod error;
+mod hazard;
+pub mod random;
+mod reclaim;
+pub mod sync;
+mod traits;
+mod waker;
+
+pub use backoff::{Backoff, Exponential, NoBackoff, Spin, SpinThenYield};
+pub use blocking::Blocking;
+pub use counter::{LenCounter, LenMode};
+pub use error::{PopError, PopTimeoutError, PushError, PushTimeoutError};
+pub use hazard::{Hazard, HazardGuard};
+pub use reclaim::{Epoch, Reclaim};
+pub use traits::{
+    BlockingConcurrentQueue, BlockingConcurrentStack, ConcurrentQueue, ConcurrentStack,
+};
+pub use waker::WakerSet;
@@ -0,0 +1,26 @@
+//! Cheap per-thread pseudo-random numbers, for spreading threads out over
+//! slots or retry delays. Not for anything that needs real randomness.
+
+use std::cell::Cell;
+
+/// Returns the next number from the calling thread's xorshift generator.
+pub fn next_u32() -> u32 {
+    thread_local! {
+        static STATE: Cell<u32> = const { Cell::new(0) };
+    }
+
+    STATE.with(|state| {
+        // Seeded from the thread-local's own address, so threads start out
+        // on different sequences.
+        let mut x = state.get();
+        if x == 0 {
+            x = (state as *const Cell<u32> as usize as u32) | 1;
+        }
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        state.set(x);
+
+        x
+    })
+}
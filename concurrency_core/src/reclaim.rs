@@ -0,0 +1,57 @@
+use crate::sync::atomic::{AtomicPtr, Ordering};
+
+/// A memory reclamation scheme for nodes unlinked from a lock-free structure.
+///
+/// Structures pin a guard for the duration of each operation, load shared
+/// pointers through [`Reclaim::protect`] before dereferencing them, and hand
+/// unlinked nodes to [`Reclaim::retire`] instead of freeing them in place.
+pub trait Reclaim {
+    type Guard;
+
+    fn pin() -> Self::Guard;
+
+    /// Loads `src` and keeps the node it points at from being freed while
+    /// `guard` is alive or until `slot` is protected again.
+    ///
+    /// Each guard has three slots, `0` to `2`.
+    fn protect<T>(guard: &Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T;
+
+    /// Frees `ptr` once no guard can still be protecting it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Box::into_raw`, must already be unreachable for
+    /// threads that pin after this call, and must not be retired twice. The
+    /// box may be dropped on any thread.
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T);
+}
+
+/// Epoch-based reclamation backed by `crossbeam-epoch`.
+///
+/// Cheap to pin and protects everything loaded while pinned, but a thread
+/// that stalls while pinned holds back all garbage retired after it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Epoch;
+
+impl Reclaim for Epoch {
+    type Guard = crossbeam_epoch::Guard;
+
+    fn pin() -> Self::Guard {
+        crossbeam_epoch::pin()
+    }
+
+    fn protect<T>(_guard: &Self::Guard, _slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        src.load(Ordering::Acquire)
+    }
+
+    #[cfg(not(loom))]
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T) {
+        guard.defer_unchecked(move || drop(Box::from_raw(ptr)));
+    }
+
+    // Loom runs every model thread on one OS thread, so crossbeam sees a
+    // single participant and could free a node another model thread still
+    // reads. Leaking keeps the model sound; nodes are tiny and models short.
+    #[cfg(loom)]
+    unsafe fn retire<T>(_guard: &Self::Guard, _ptr: *mut T) {}
+}
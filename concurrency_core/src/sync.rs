@@ -0,0 +1,48 @@
+//! The synchronisation primitives the structures in this workspace are built
+//! on.
+//!
+//! Building with `RUSTFLAGS="--cfg loom"` swaps them for their `loom`
+//! counterparts so the model checker can explore every interleaving of the
+//! code that uses them. Anything that should be model-checked must import
+//! these instead of the `std` versions, including [`cell::UnsafeCell`] for
+//! values that are written by one thread and read by another.
+
+#[cfg(loom)]
+pub use loom::sync::{atomic, Arc, Condvar, Mutex, MutexGuard};
+#[cfg(loom)]
+pub use loom::thread::yield_now;
+#[cfg(loom)]
+pub use loom::{cell, hint};
+
+#[cfg(not(loom))]
+pub use std::hint;
+#[cfg(not(loom))]
+pub use std::sync::{atomic, Arc, Condvar, Mutex, MutexGuard};
+#[cfg(not(loom))]
+pub use std::thread::yield_now;
+
+#[cfg(not(loom))]
+pub mod cell {
+    /// `std::cell::UnsafeCell` behind loom's closure-based API, so every
+    /// access can be tracked when the model checker is swapped in.
+    #[derive(Debug, Default)]
+    pub struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+    impl<T> UnsafeCell<T> {
+        pub const fn new(data: T) -> Self {
+            Self(std::cell::UnsafeCell::new(data))
+        }
+
+        pub fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+            f(self.0.get())
+        }
+
+        pub fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.0.get())
+        }
+
+        pub fn into_inner(self) -> T {
+            self.0.into_inner()
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{PopError, PushError};
+
+/// A FIFO structure that can be shared between threads.
+///
+/// `len` is a snapshot and may already be stale when it returns if other
+/// threads are pushing or popping concurrently.
+pub trait ConcurrentQueue<T> {
+    /// Pushes `item` without blocking, handing it back if it can't be accepted.
+    fn try_push(&self, item: T) -> Result<(), T>;
+
+    /// Pops the oldest item without blocking.
+    fn try_pop(&self) -> Option<T>;
+
+    fn is_empty(&self) -> bool;
+
+    fn len(&self) -> usize;
+}
+
+/// A LIFO structure that can be shared between threads.
+///
+/// `len` is a snapshot and may already be stale when it returns if other
+/// threads are pushing or popping concurrently.
+pub trait ConcurrentStack<T> {
+    /// Pushes `item` without blocking, handing it back if it can't be accepted.
+    fn try_push(&self, item: T) -> Result<(), T>;
+
+    /// Pops the newest item without blocking.
+    fn try_pop(&self) -> Option<T>;
+
+    fn is_empty(&self) -> bool;
+
+    fn len(&self) -> usize;
+}
+
+/// A [`ConcurrentQueue`] whose `push` and `pop` can wait for room or items.
+pub trait BlockingConcurrentQueue<T>: ConcurrentQueue<T> {
+    /// Waits for room, then pushes `item`. Fails once the structure is closed.
+    fn push(&self, item: T) -> Result<(), PushError<T>>;
+
+    /// Waits for an item. Once the structure is closed this keeps returning
+    /// the remaining items and then fails.
+    fn pop(&self) -> Result<T, PopError>;
+}
+
+/// A [`ConcurrentStack`] whose `push` and `pop` can wait for room or items.
+pub trait BlockingConcurrentStack<T>: ConcurrentStack<T> {
+    /// Waits for room, then pushes `item`. Fails once the structure is closed.
+    fn push(&self, item: T) -> Result<(), PushError<T>>;
+
+    /// Waits for an item. Once the structure is closed this keeps returning
+    /// the remaining items and then fails.
+    fn pop(&self) -> Result<T, PopError>;
+}
@@ -0,0 +1,136 @@
+use crate::sync::atomic::{fence, AtomicUsize, Ordering};
+use crate::sync::Mutex;
+use std::fmt;
+use std::task::Waker;
+
+/// A list of tasks waiting for a structure to change, for building futures
+/// on top of the structures in this workspace.
+///
+/// A future registers its waker under a key it keeps between polls and
+/// checks its condition again afterwards. Anything that might satisfy a
+/// waiter calls `notify_one` or `notify_all` once its change is visible.
+/// Notifying with nobody registered costs a fence and a load.
+///
+/// A notified waiter is removed from the list. If a future is dropped after
+/// being notified but before acting on it, [`WakerSet::cancel`] passes the
+/// wakeup on so it isn't lost.
+pub struct WakerSet {
+    entries: Mutex<Entries>,
+    registered: AtomicUsize,
+}
+
+struct Entries {
+    next_key: usize,
+    wakers: Vec<(usize, Waker)>,
+}
+
+impl WakerSet {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                next_key: 0,
+                wakers: Vec::new(),
+            }),
+            registered: AtomicUsize::new(0),
+        }
+    }
+
+    /// Registers `waker`, or refreshes it if `key` is still registered.
+    /// The caller must check its condition again after this returns.
+    pub fn register(&self, key: &mut Option<usize>, waker: &Waker) {
+        let mut entries = self.entries.lock().unwrap();
+        let existing = key.and_then(|key| entries.wakers.iter_mut().find(|(k, _)| *k == key));
+        match existing {
+            Some((_, registered)) => {
+                if !registered.will_wake(waker) {
+                    registered.clone_from(waker);
+                }
+            }
+            None => {
+                let new_key = entries.next_key;
+                entries.next_key = entries.next_key.wrapping_add(1);
+                entries.wakers.push((new_key, waker.clone()));
+                self.registered
+                    .store(entries.wakers.len(), Ordering::Relaxed);
+                *key = Some(new_key);
+            }
+        }
+        drop(entries);
+        // Pairs with the fence in `has_waiters`: either the caller's next check
+        // sees the notifier's change or the notifier sees this entry.
+        fence(Ordering::SeqCst);
+    }
+
+    /// Drops the registration under `key` once the waiting operation has
+    /// gone through. Returns `false` if it had already been notified.
+    pub fn remove(&self, key: &mut Option<usize>) -> bool {
+        let Some(key) = key.take() else {
+            return false;
+        };
+        let mut entries = self.entries.lock().unwrap();
+        let Some(index) = entries.wakers.iter().position(|(k, _)| *k == key) else {
+            return false;
+        };
+        entries.wakers.remove(index);
+        self.registered
+            .store(entries.wakers.len(), Ordering::Relaxed);
+
+        true
+    }
+
+    /// Drops the registration under `key` for a waiter that is giving up,
+    /// waking someone else if it had already been notified.
+    pub fn cancel(&self, key: &mut Option<usize>) {
+        if key.is_some() && !self.remove(key) {
+            self.notify_one();
+        }
+    }
+
+    pub fn notify_one(&self) {
+        if !self.has_waiters() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.wakers.is_empty() {
+            return;
+        }
+        let (_, waker) = entries.wakers.remove(0);
+        self.registered
+            .store(entries.wakers.len(), Ordering::Relaxed);
+        drop(entries);
+        waker.wake();
+    }
+
+    pub fn notify_all(&self) {
+        if !self.has_waiters() {
+            return;
+        }
+        let wakers = {
+            let mut entries = self.entries.lock().unwrap();
+            self.registered.store(0, Ordering::Relaxed);
+            std::mem::take(&mut entries.wakers)
+        };
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    fn has_waiters(&self) -> bool {
+        fence(Ordering::SeqCst);
+        self.registered.load(Ordering::Relaxed) != 0
+    }
+}
+
+impl Default for WakerSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for WakerSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WakerSet")
+            .field("registered", &self.registered.load(Ordering::Relaxed))
+            .finish()
+    }
+}
@@ -0,0 +1,84 @@
+use concurrency_core::{LenCounter, LenMode};
+use std::thread;
+
+const THREADS: usize = 4;
+const OPS_PER_THREAD: usize = 10_000;
+
+#[test]
+fn counts_adds_and_subs_in_both_modes() {
+    for mode in [LenMode::Approximate, LenMode::Exact] {
+        let counter = LenCounter::new(mode);
+        assert_eq!(counter.mode(), mode);
+        assert_eq!(counter.get(), 0);
+
+        counter.add(5);
+        counter.sub(2);
+        counter.add(1);
+        assert_eq!(counter.get(), 4);
+    }
+}
+
+#[test]
+fn default_is_approximate() {
+    assert_eq!(LenCounter::default().mode(), LenMode::Approximate);
+    assert_eq!(LenMode::default(), LenMode::Approximate);
+}
+
+#[test]
+fn get_never_goes_below_zero() {
+    // A pop can be counted on another stripe before the push it took the
+    // item from, so the stripes can briefly sum to less than zero.
+    for mode in [LenMode::Approximate, LenMode::Exact] {
+        let counter = LenCounter::new(mode);
+        counter.sub(3);
+        assert_eq!(counter.get(), 0);
+
+        counter.add(4);
+        assert_eq!(counter.get(), 1);
+    }
+}
+
+#[test]
+fn counts_from_many_threads_add_up_once_they_finish() {
+    for mode in [LenMode::Approximate, LenMode::Exact] {
+        let counter = LenCounter::new(mode);
+        thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let counter = &counter;
+                scope.spawn(move || {
+                    for _ in 0..OPS_PER_THREAD {
+                        counter.add(2);
+                        counter.sub(1);
+                    }
+                    // Odd threads take back what they added, so some
+                    // stripes end at zero and others don't.
+                    if thread % 2 == 1 {
+                        counter.sub(OPS_PER_THREAD);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(counter.get(), THREADS / 2 * OPS_PER_THREAD);
+    }
+}
+
+#[test]
+fn exact_mode_only_grows_while_threads_only_add() {
+    let counter = LenCounter::new(LenMode::Exact);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..OPS_PER_THREAD {
+                    counter.add(1);
+                }
+            });
+        }
+        let mut last = 0;
+        while last < THREADS * OPS_PER_THREAD {
+            let now = counter.get();
+            assert!(now >= last, "went from {last} to {now}");
+            last = now;
+        }
+    });
+}
@@ -0,0 +1,123 @@
+use concurrency_core::sync::atomic::AtomicPtr;
+use concurrency_core::{Hazard, Reclaim};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, Mutex, PoisonError};
+use std::thread;
+
+// Mirrors the retire-list length at which `hazard.rs` scans. With as few
+// threads as these tests use, twice the hazard slots stays below it.
+const SCAN_THRESHOLD: usize = 64;
+
+// Orphaned lists are adopted by whichever thread scans next, so tests that
+// count frees mustn't run at the same time.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+struct Tracked(Arc<AtomicUsize>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn tracked(freed: &Arc<AtomicUsize>) -> *mut Tracked {
+    Box::into_raw(Box::new(Tracked(Arc::clone(freed))))
+}
+
+fn retire(node: *mut Tracked) {
+    let guard = Hazard::pin();
+    unsafe { Hazard::retire(&guard, node) };
+}
+
+#[test]
+fn stalled_guard_keeps_garbage_bounded() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let protected_freed = Arc::new(AtomicUsize::new(0));
+    let shared = AtomicPtr::new(tracked(&protected_freed));
+    let protected = Barrier::new(2);
+    let churned = Barrier::new(2);
+    let released = Barrier::new(2);
+
+    // Everything is checked once all barriers are through, so a failure
+    // can't leave the other thread waiting.
+    let (most_unfreed, freed_while_protected) = thread::scope(|scope| {
+        scope.spawn(|| {
+            let guard = Hazard::pin();
+            Hazard::protect(&guard, 0, &shared);
+            protected.wait();
+            churned.wait();
+            drop(guard);
+            released.wait();
+        });
+
+        protected.wait();
+        retire(shared.swap(ptr::null_mut(), Ordering::AcqRel));
+
+        let churners: Vec<_> = (0..3)
+            .map(|_| {
+                scope.spawn(|| {
+                    let freed = Arc::new(AtomicUsize::new(0));
+                    (1..=10_000)
+                        .map(|retired| {
+                            retire(tracked(&freed));
+                            retired - freed.load(Ordering::Relaxed)
+                        })
+                        .max()
+                        .unwrap()
+                })
+            })
+            .collect();
+        let most_unfreed = churners.into_iter().map(|c| c.join().unwrap()).max();
+        for _ in 0..2 * SCAN_THRESHOLD {
+            retire(tracked(&Arc::new(AtomicUsize::new(0))));
+        }
+        let freed_while_protected = protected_freed.load(Ordering::Relaxed);
+
+        churned.wait();
+        released.wait();
+        (most_unfreed.unwrap(), freed_while_protected)
+    });
+
+    assert!(
+        most_unfreed < SCAN_THRESHOLD,
+        "{most_unfreed} nodes left unfreed"
+    );
+    assert_eq!(freed_while_protected, 0);
+    Hazard::flush();
+    assert_eq!(protected_freed.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn exited_threads_leave_their_garbage_for_others() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let protected_freed = Arc::new(AtomicUsize::new(0));
+    let shared = Arc::new(AtomicPtr::new(tracked(&protected_freed)));
+    let guard = Hazard::pin();
+    Hazard::protect(&guard, 0, &shared);
+
+    // A joined thread has run its thread-local destructors, which is where
+    // it hands its list over.
+    let worker = {
+        let (shared, protected_freed) = (Arc::clone(&shared), Arc::clone(&protected_freed));
+        thread::spawn(move || {
+            retire(shared.swap(ptr::null_mut(), Ordering::AcqRel));
+            let freed = Arc::new(AtomicUsize::new(0));
+            for _ in 0..10 {
+                retire(tracked(&freed));
+            }
+            assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+            Hazard::flush();
+            assert_eq!(freed.load(Ordering::Relaxed), 10);
+            assert_eq!(protected_freed.load(Ordering::Relaxed), 0);
+        })
+    };
+    worker.join().unwrap();
+
+    // The worker exited with the protected node still on its list.
+    assert_eq!(protected_freed.load(Ordering::Relaxed), 0);
+    drop(guard);
+    Hazard::flush();
+    assert_eq!(protected_freed.load(Ordering::Relaxed), 1);
+}
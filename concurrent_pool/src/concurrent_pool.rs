@@ -1,10 +1,18 @@
+use crate::Pooled;
+use concurrency_core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use concurrency_core::sync::cell::UnsafeCell;
+use concurrency_core::{Backoff, ConcurrentQueue, Epoch, Reclaim, SpinThenYield};
 use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
 use std::ptr;
-use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
 
+// `data` is initialised for every node reachable from `head.next`. The node
+// `head` points at is the sentinel, whose data has either never been written
+// or has already been moved out by the `pop` that made it the sentinel.
 struct Node<T> {
     next: AtomicPtr<Node<T>>,
-    data: T,
+    data: UnsafeCell<MaybeUninit<T>>,
 }
 
 #[derive(Debug)]
@@ -12,16 +20,59 @@ pub enum PoolError {
     Empty,
 }
 
-pub struct ConcurrentPool<T> {
+// How often `iter` starts its walk over before it settles for a partial
+// snapshot.
+const MAX_ITER_RESTARTS: usize = 16;
+
+type Factory<T> = Box<dyn Fn() -> T + Send + Sync>;
+type Reset<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+pub struct ConcurrentPool<T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
     head: AtomicPtr<Node<T>>,
     tail: AtomicPtr<Node<T>>,
     size: AtomicUsize,
+    max_size: usize,
+    factory: Option<Factory<T>>,
+    reset: Option<Reset<T>>,
+    backoff: B,
+    _reclaim: PhantomData<R>,
 }
 
 impl<T> ConcurrentPool<T> {
     pub fn new() -> Self {
+        Self::with_reclaim(Epoch)
+    }
+
+    /// Creates a pool that holds at most `max_size` items. Once it's full,
+    /// `push` hands the value back.
+    pub fn bounded(max_size: usize) -> Self {
+        Self::new().with_max_size(max_size)
+    }
+
+    /// Creates an object pool. `get` hands out an idle object, or a new one
+    /// from `factory` if there is none, and the returned guard gives it back
+    /// on drop. At most `max` idle objects are kept; objects returned beyond
+    /// that are dropped.
+    pub fn with_factory<F>(max: usize, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Self::bounded(max).with_object_factory(factory)
+    }
+}
+
+impl<T, R: Reclaim> ConcurrentPool<T, R> {
+    /// Creates a pool that frees popped nodes through `R`, e.g.
+    /// `ConcurrentPool::with_reclaim(Hazard)`.
+    pub fn with_reclaim(_reclaim: R) -> Self {
+        Self::with_parts(SpinThenYield::default())
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> ConcurrentPool<T, R, B> {
+    fn with_parts(backoff: B) -> Self {
         let sentinel_node = Box::into_raw(Box::new(Node {
-            data: unsafe { std::mem::zeroed() },
+            data: UnsafeCell::new(MaybeUninit::uninit()),
             next: AtomicPtr::new(ptr::null_mut()),
         }));
 
@@ -29,17 +80,116 @@ impl<T> ConcurrentPool<T> {
             head: AtomicPtr::new(sentinel_node),
             tail: AtomicPtr::new(sentinel_node),
             size: AtomicUsize::new(0),
+            max_size: usize::MAX,
+            factory: None,
+            reset: None,
+            backoff,
+            _reclaim: PhantomData,
         }
     }
 
+    /// Switches to another [`Backoff`] strategy for retrying after a lost
+    /// race, e.g. `ConcurrentPool::bounded(64).with_backoff(Spin::new(4))`.
+    /// The bound, factory, reset hook and pooled items all carry over.
+    pub fn with_backoff<B2: Backoff>(mut self, backoff: B2) -> ConcurrentPool<T, R, B2> {
+        let mut pool = ConcurrentPool::with_parts(backoff);
+        pool.max_size = self.max_size;
+        pool.factory = self.factory.take();
+        pool.reset = self.reset.take();
+        pool.push_range(self.drain());
+
+        pool
+    }
+
+    /// Limits the pool to `max_size` items, like [`ConcurrentPool::bounded`]
+    /// but for any reclaimer, e.g.
+    /// `ConcurrentPool::with_reclaim(Hazard).with_max_size(64)`. Items
+    /// already in the pool stay even if there are more of them.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Makes `get` create objects with `factory` when the pool is empty,
+    /// like [`ConcurrentPool::with_factory`] but for any reclaimer and
+    /// without changing the bound, e.g.
+    /// `ConcurrentPool::with_reclaim(Hazard).with_max_size(8).with_object_factory(String::new)`.
+    pub fn with_object_factory<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        self.factory = Some(Box::new(factory));
+        self
+    }
+
+    /// Runs `reset` on every object handed back by a [`Pooled`] guard before
+    /// it goes back into the pool, e.g. `Vec::clear` for scratch buffers.
+    pub fn with_reset<F>(mut self, reset: F) -> Self
+    where
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.reset = Some(Box::new(reset));
+        self
+    }
+
+    /// Takes an idle object out of the pool, or makes a new one with the
+    /// factory if there is none. The guard puts it back when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pool is empty and wasn't built with
+    /// [`ConcurrentPool::with_factory`]. Use [`ConcurrentPool::try_get`] to
+    /// only reuse existing objects.
+    pub fn get(&self) -> Pooled<'_, T, R, B> {
+        if let Some(pooled) = self.try_get() {
+            return pooled;
+        }
+        let factory = self
+            .factory
+            .as_ref()
+            .expect("ConcurrentPool::get on an empty pool without a factory");
+
+        Pooled::new(self, factory())
+    }
+
+    /// Takes an idle object out of the pool without ever creating one.
+    pub fn try_get(&self) -> Option<Pooled<'_, T, R, B>> {
+        self.try_pop().map(|value| Pooled::new(self, value))
+    }
+
+    pub(crate) fn recycle(&self, mut value: T) {
+        if let Some(reset) = &self.reset {
+            reset(&mut value);
+        }
+        // Dropped if the pool already holds `max_size` idle objects.
+        let _ = self.push(value);
+    }
+
+    /// Hands `val` back if the pool already holds `max_size` items.
     pub fn push(&self, val: T) -> Result<(), T> {
+        // Claim a slot before linking, so racing pushers can't overshoot
+        // the limit. `pop` only gives a slot back after unlinking a node,
+        // which keeps `size` from underflowing.
+        let max_size = self.max_size;
+        if self
+            .size
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                (size < max_size).then_some(size + 1)
+            })
+            .is_err()
+        {
+            return Err(val);
+        }
+
         let node = Box::into_raw(Box::new(Node {
-            data: val,
+            data: UnsafeCell::new(MaybeUninit::new(val)),
             next: AtomicPtr::new(ptr::null_mut()),
         }));
 
+        let guard = &R::pin();
+        let mut step = 0;
         loop {
-            let tail = self.tail.load(Ordering::Acquire);
+            let tail = R::protect(guard, 0, &self.tail);
             let next = unsafe { (*tail).next.load(Ordering::Acquire) };
             if next.is_null() {
                 if unsafe {
@@ -53,9 +203,11 @@ impl<T> ConcurrentPool<T> {
                         .compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed)
                         .ok();
 
-                    self.size.fetch_add(1, Ordering::Relaxed);
                     return Ok(());
                 }
+
+                self.backoff.wait(step);
+                step = step.saturating_add(1);
             } else {
                 self.tail
                     .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed)
@@ -65,38 +217,61 @@ impl<T> ConcurrentPool<T> {
     }
 
     pub fn pop(&self) -> Result<T, PoolError> {
+        let guard = &R::pin();
+        let mut step = 0;
         loop {
-            let head = self.head.load(Ordering::Acquire);
-            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            let head = R::protect(guard, 0, &self.head);
+            let next = R::protect(guard, 1, unsafe { &(*head).next });
             if next.is_null() {
                 return Err(PoolError::Empty);
             }
 
-            if self
-                .head
-                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed)
-                .is_ok()
+            if self.head.load(Ordering::Acquire) == head
+                && self
+                    .head
+                    .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
             {
+                let tail = self.tail.load(Ordering::Relaxed);
+                if tail == head {
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed)
+                        .ok();
+                }
+
                 unsafe {
-                    let data = ptr::read(&(*next).data);
-                    std::mem::drop(Box::from_raw(head));
+                    let data = (*next).data.with(|data| (*data).assume_init_read());
+                    R::retire(guard, head);
                     self.size.fetch_sub(1, Ordering::Relaxed);
                     return Ok(data);
                 }
             }
+
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
-    pub fn peek(&self) -> Option<&T> {
+    /// Returns a copy of the front element without removing it.
+    ///
+    /// A concurrent `pop` moves the value out of its node and may drop it, so
+    /// only `Copy` values can be read safely after that, hence the bound. For
+    /// other values, `try_pop` and push the value back.
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let guard = &R::pin();
         loop {
-            let head = self.head.load(Ordering::Acquire);
-            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            let head = R::protect(guard, 0, &self.head);
+            let next = R::protect(guard, 1, unsafe { &(*head).next });
             if next.is_null() {
                 return None;
             }
 
-            if head == self.head.load(Ordering::Relaxed) {
-                return Some(unsafe { &(*head).data });
+            // `next` only stays linked while `head` is still the sentinel.
+            if self.head.load(Ordering::Acquire) == head {
+                return Some(unsafe { (*next).data.with(|data| (*data).assume_init_read()) });
             }
         }
     }
@@ -105,6 +280,10 @@ impl<T> ConcurrentPool<T> {
         while self.pop().is_ok() {}
     }
 
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
     pub fn is_empty(&self) -> bool {
         self.size.load(Ordering::Relaxed) == 0
     }
@@ -117,6 +296,8 @@ impl<T> ConcurrentPool<T> {
         self.pop().ok()
     }
 
+    /// Pushes every item that fits and returns how many did. Items that
+    /// don't fit are dropped.
     pub fn push_range<I>(&self, iter: I) -> usize
     where
         I: IntoIterator<Item = T>,
@@ -131,6 +312,64 @@ impl<T> ConcurrentPool<T> {
         count
     }
 
+    /// Pops items until the pool is empty. Items pushed by other threads
+    /// while draining are popped too, and anything left when the iterator is
+    /// dropped stays in the pool.
+    pub fn drain(&self) -> Drain<'_, T, R, B> {
+        Drain { pool: self }
+    }
+
+    /// Copies the current contents, front first, without removing them.
+    ///
+    /// The walk starts over whenever the front is popped underneath it, so
+    /// the snapshot usually matches the pool as it was at some instant
+    /// during the call. Under a steady stream of pops that could go on
+    /// forever, so after `MAX_ITER_RESTARTS` restarts it settles for what
+    /// the last walk saw before the front moved: items in pool order, each
+    /// of which was in the pool during the call, but possibly only a prefix
+    /// of it. Values are copied rather than borrowed because a concurrent
+    /// `pop` moves them out of their nodes.
+    pub fn iter(&self) -> Iter<T>
+    where
+        T: Copy,
+    {
+        let guard = &R::pin();
+        let mut items = Vec::with_capacity(self.len());
+        let mut restarts = 0;
+        'retry: loop {
+            items.clear();
+            let head = R::protect(guard, 0, &self.head);
+
+            // While `head` is still the sentinel nothing after it can have
+            // been popped, so every node reached from it is still linked and
+            // safe to read once it's protected. Slots 1 and 2 alternate so
+            // the node being read from stays protected.
+            let mut current = head;
+            let mut slot = 1;
+            loop {
+                let next = R::protect(guard, slot, unsafe { &(*current).next });
+                if self.head.load(Ordering::Acquire) != head {
+                    if restarts == MAX_ITER_RESTARTS {
+                        break 'retry;
+                    }
+                    restarts += 1;
+                    continue 'retry;
+                }
+                if next.is_null() {
+                    break 'retry;
+                }
+
+                items.push(unsafe { (*next).data.with(|data| (*data).assume_init_read()) });
+                current = next;
+                slot = 3 - slot;
+            }
+        }
+
+        Iter {
+            items: items.into_iter(),
+        }
+    }
+
     pub fn pop_range(&self, n: usize) -> Vec<T> {
         let mut res = Vec::with_capacity(n);
         for _ in 0..n {
@@ -145,11 +384,11 @@ impl<T> ConcurrentPool<T> {
     }
 }
 
-pub struct Drain<'a, T> {
-    pool: &'a ConcurrentPool<T>,
+pub struct Drain<'a, T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
+    pool: &'a ConcurrentPool<T, R, B>,
 }
 
-impl<'a, T> Iterator for Drain<'a, T> {
+impl<'a, T, R: Reclaim, B: Backoff> Iterator for Drain<'a, T, R, B> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -157,52 +396,112 @@ impl<'a, T> Iterator for Drain<'a, T> {
     }
 }
 
-pub struct Iter<'a, T> {
-    curr: *const Node<T>,
-    _marker: std::marker::PhantomData<&'a T>,
+/// A snapshot of the pool's contents, returned by [`ConcurrentPool::iter`].
+pub struct Iter<T> {
+    items: std::vec::IntoIter<T>,
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+impl<T> Iterator for Iter<T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr.is_null() {
-            None
-        } else {
-            unsafe {
-                let res = &(*self.curr).data;
-                self.curr = (*self.curr).next.load(Ordering::Acquire);
-                Some(res)
-            }
-        }
+        self.items.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<T> {}
+
+pub struct IntoIter<T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
+    pool: ConcurrentPool<T, R, B>,
+}
+
+impl<T, R: Reclaim, B: Backoff> Iterator for IntoIter<T, R, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pool.try_pop()
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> IntoIterator for ConcurrentPool<T, R, B> {
+    type Item = T;
+    type IntoIter = IntoIter<T, R, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { pool: self }
     }
 }
 
-impl<T> FromIterator<T> for ConcurrentPool<T> {
+impl<T: Copy, R: Reclaim, B: Backoff> IntoIterator for &ConcurrentPool<T, R, B> {
+    type Item = T;
+    type IntoIter = Iter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, R: Reclaim + Default, B: Backoff + Default> FromIterator<T> for ConcurrentPool<T, R, B> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let pool = ConcurrentPool::new();
+        let pool = ConcurrentPool::default();
         pool.push_range(iter);
 
         pool
     }
 }
 
-impl<T: Debug> Debug for ConcurrentPool<T> {
+impl<T, R: Reclaim, B: Backoff> ConcurrentQueue<T> for ConcurrentPool<T, R, B> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.push(item)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, R: Reclaim + Default, B: Backoff + Default> Default for ConcurrentPool<T, R, B> {
+    fn default() -> Self {
+        Self::with_parts(B::default())
+    }
+}
+
+impl<T: Debug, R: Reclaim, B: Backoff> Debug for ConcurrentPool<T, R, B> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ConcurrentPool")
             .field("head", &self.head)
             .field("tail", &self.tail)
             .field("size", &self.size)
+            .field("max_size", &self.max_size)
             .finish()
     }
 }
 
-impl<T> Drop for ConcurrentPool<T> {
+impl<T, R: Reclaim, B: Backoff> Drop for ConcurrentPool<T, R, B> {
     fn drop(&mut self) {
-        self.clear();
-        let head = self.head.load(Ordering::Relaxed);
-        unsafe {
-            drop(Box::from_raw(head));
+        let mut current = self.head.load(Ordering::Relaxed);
+        let sentinel = unsafe { Box::from_raw(current) };
+        current = sentinel.next.load(Ordering::Relaxed);
+        while !current.is_null() {
+            let node = unsafe { Box::from_raw(current) };
+            node.data
+                .with_mut(|data| unsafe { (*data).assume_init_drop() });
+            current = node.next.load(Ordering::Relaxed);
         }
     }
 }
+
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Send> Send for ConcurrentPool<T, R, B> {}
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Sync> Sync for ConcurrentPool<T, R, B> {}
@@ -0,0 +1,9 @@
+mod concurrent_pool;
+mod pooled;
+
+pub use concurrency_core::{
+    Backoff, Blocking, BlockingConcurrentQueue, ConcurrentQueue, Epoch, Exponential, Hazard,
+    NoBackoff, Reclaim, Spin, SpinThenYield,
+};
+pub use concurrent_pool::{ConcurrentPool, Drain, IntoIter, Iter, PoolError};
+pub use pooled::Pooled;
@@ -1,5 +1,3 @@
-mod concurrent_pool;
-
 use std::thread;
 use std::time::Duration;
 
@@ -75,4 +73,93 @@ fn main() {
     let new_pool: ConcurrentPool<i32> = vec.into_iter().collect();
     println!("New pool from iterator, size: {}", new_pool.len());
     println!("Debug output of pool: {:?}", new_pool);
+
+    println!("Object pool of scratch buffers");
+    let buffers = ConcurrentPool::with_factory(2, || [0u8; 16]).with_reset(|buf| buf.fill(0));
+    {
+        let mut buf = buffers.get();
+        buf[0] = 42;
+        println!("Borrowed buffer: {:?}", buf);
+    }
+    assert_eq!(buffers.len(), 1);
+    assert_eq!(buffers.get()[0], 0);
+
+    let first = buffers.get();
+    let second = buffers.get();
+    let third = buffers.get();
+    drop((first, second, third));
+    assert_eq!(buffers.len(), 2);
+
+    let detached = buffers.get().detach();
+    assert_eq!(detached, [0u8; 16]);
+    assert_eq!(buffers.len(), 1);
+    println!("Idle buffers after detach: {}", buffers.len());
+
+    println!("Bounded pool");
+    let bounded = std::sync::Arc::new(ConcurrentPool::bounded(100));
+    let pushers: Vec<_> = (0..8)
+        .map(|t| {
+            let pool = bounded.clone();
+            thread::spawn(move || (0..50).filter(|i| pool.push(t * 50 + i).is_ok()).count())
+        })
+        .collect();
+    let accepted: usize = pushers.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(accepted, 100);
+    assert_eq!(bounded.len(), 100);
+    assert_eq!(bounded.push(-1), Err(-1));
+    assert_eq!(bounded.push_range(0..10), 0);
+    bounded.pop().unwrap();
+    assert_eq!(bounded.push(-1), Ok(()));
+    println!("Bounded pool accepted {} of 400 pushes", accepted);
+
+    println!("Pools of owning types");
+    let strings = ConcurrentPool::new();
+    strings.push(String::from("first")).unwrap();
+    strings.push(String::from("second")).unwrap();
+    assert_eq!(strings.try_pop().as_deref(), Some("first"));
+    drop(strings);
+
+    let boxes = ConcurrentPool::new();
+    boxes.push_range((0..3).map(Box::new));
+    assert_eq!(boxes.try_pop().map(|b| *b), Some(0));
+    assert_eq!(boxes.len(), 2);
+
+    let front = ConcurrentPool::new();
+    front.push_range([7, 8, 9]);
+    assert_eq!(front.peek(), Some(7));
+    println!("Front of the pool: {:?}", front.peek());
+
+    let buffers = ConcurrentPool::with_factory(4, Vec::<u8>::new).with_reset(Vec::clear);
+    buffers.get().extend_from_slice(b"scratch");
+    assert!(buffers.get().is_empty());
+
+    println!("Iterating and draining");
+    let pool: ConcurrentPool<i32> = (1..=5).collect();
+    assert_eq!(pool.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    assert_eq!((&pool).into_iter().sum::<i32>(), 15);
+    assert_eq!(pool.len(), 5);
+    assert_eq!(pool.drain().take(2).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.into_iter().collect::<Vec<_>>(), vec![3, 4, 5]);
+
+    let pool = std::sync::Arc::new(ConcurrentPool::new());
+    let pusher = {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            for i in 0..10_000 {
+                pool.push(i).unwrap();
+            }
+        })
+    };
+    let mut drained = 0;
+    while !pusher.is_finished() {
+        let snapshot: Vec<_> = pool.iter().collect();
+        assert!(snapshot.windows(2).all(|w| w[0] < w[1]));
+        drained += pool.drain().count();
+    }
+    pusher.join().unwrap();
+    drained += pool.drain().count();
+    assert_eq!(drained, 10_000);
+    assert_eq!(pool.len(), 0);
+    println!("Drained {} items while pushing", drained);
 }
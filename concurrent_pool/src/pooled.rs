@@ -0,0 +1,56 @@
+use crate::ConcurrentPool;
+use concurrency_core::{Backoff, Epoch, Reclaim, SpinThenYield};
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+
+/// An object borrowed from a [`ConcurrentPool`], returned by
+/// [`ConcurrentPool::get`]. Dropping it runs the pool's reset hook and puts
+/// the object back.
+pub struct Pooled<'a, T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
+    pool: &'a ConcurrentPool<T, R, B>,
+    value: ManuallyDrop<T>,
+}
+
+impl<'a, T, R: Reclaim, B: Backoff> Pooled<'a, T, R, B> {
+    pub(crate) fn new(pool: &'a ConcurrentPool<T, R, B>, value: T) -> Self {
+        Self {
+            pool,
+            value: ManuallyDrop::new(value),
+        }
+    }
+
+    /// Takes the object out of the pool for good. The reset hook doesn't
+    /// run and the pool won't see it again.
+    pub fn detach(self) -> T {
+        let mut this = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> Deref for Pooled<'_, T, R, B> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> DerefMut for Pooled<'_, T, R, B> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> Drop for Pooled<'_, T, R, B> {
+    fn drop(&mut self) {
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        self.pool.recycle(value);
+    }
+}
+
+impl<T: fmt::Debug, R: Reclaim, B: Backoff> fmt::Debug for Pooled<'_, T, R, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pooled").field(&*self.value).finish()
+    }
+}
@@ -0,0 +1,94 @@
+use concurrent_pool::{ConcurrentPool, Hazard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+const THREADS: u32 = 4;
+const PUSHES_PER_THREAD: u32 = 1_000;
+const MAX_SIZE: usize = 64;
+
+#[test]
+fn full_pool_hands_the_value_back() {
+    let pool = ConcurrentPool::bounded(2);
+    assert_eq!(pool.push("a"), Ok(()));
+    assert_eq!(pool.push("b"), Ok(()));
+    assert_eq!(pool.push("c"), Err("c"));
+
+    assert_eq!(pool.try_pop(), Some("a"));
+    assert_eq!(pool.push("c"), Ok(()));
+    assert_eq!(pool.drain().collect::<Vec<_>>(), ["b", "c"]);
+}
+
+#[test]
+fn push_range_counts_what_fit() {
+    let pool = ConcurrentPool::bounded(3);
+    assert_eq!(pool.push_range(1..=2), 2);
+    assert_eq!(pool.push_range(3..=6), 1);
+    assert_eq!(pool.iter().collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[test]
+fn zero_max_size_rejects_everything() {
+    let pool = ConcurrentPool::bounded(0);
+    assert_eq!(pool.push(1), Err(1));
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn racing_pushes_fill_the_pool_exactly() {
+    let pool = ConcurrentPool::with_reclaim(Hazard).with_max_size(MAX_SIZE);
+    let rejected: Vec<u32> = thread::scope(|scope| {
+        let pushers: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let pool = &pool;
+                scope.spawn(move || {
+                    let first = thread * PUSHES_PER_THREAD;
+                    (first..first + PUSHES_PER_THREAD)
+                        .filter_map(|value| pool.push(value).err())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        pushers
+            .into_iter()
+            .flat_map(|pusher| pusher.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(pool.len(), MAX_SIZE);
+    let mut all: Vec<u32> = pool.drain().chain(rejected).collect();
+    all.sort();
+    assert_eq!(all, (0..THREADS * PUSHES_PER_THREAD).collect::<Vec<_>>());
+}
+
+#[test]
+fn len_never_exceeds_max_size_under_churn() {
+    let pool = ConcurrentPool::bounded(MAX_SIZE);
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let observer = scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                assert!(pool.len() <= MAX_SIZE);
+                assert!(pool.iter().len() <= MAX_SIZE);
+            }
+        });
+        let churners: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let pool = &pool;
+                scope.spawn(move || {
+                    for value in 0..PUSHES_PER_THREAD {
+                        let _ = pool.push(value);
+                        if (value + thread) % 3 == 0 {
+                            pool.try_pop();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for churner in churners {
+            churner.join().unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        observer.join().unwrap();
+    });
+}
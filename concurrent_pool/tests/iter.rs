@@ -0,0 +1,101 @@
+use concurrent_pool::{ConcurrentPool, Hazard, Reclaim};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+const ITEMS: u32 = 20_000;
+
+// Every snapshot of a pool fed `0, 1, 2, ...` in order has to be a run of
+// consecutive values, whatever was pushed or popped while it was taken.
+fn assert_consecutive(snapshot: &[u32]) {
+    for pair in snapshot.windows(2) {
+        assert_eq!(
+            pair[1],
+            pair[0] + 1,
+            "snapshot {snapshot:?} skips or reorders"
+        );
+    }
+}
+
+fn iterate_while_pushing<R: Reclaim>(pool: ConcurrentPool<u32, R>, pop: bool) {
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for value in 0..ITEMS {
+                pool.push(value).unwrap();
+            }
+            done.store(true, Ordering::Release);
+        });
+        if pop {
+            scope.spawn(|| {
+                while !done.load(Ordering::Acquire) {
+                    pool.try_pop();
+                }
+            });
+        }
+
+        let mut snapshots = 0;
+        while !done.load(Ordering::Acquire) || snapshots == 0 {
+            let snapshot: Vec<u32> = pool.iter().collect();
+            assert_consecutive(&snapshot);
+            if !pop {
+                assert_eq!(snapshot.first().copied().unwrap_or(0), 0);
+            }
+            snapshots += 1;
+        }
+    });
+}
+
+#[test]
+fn empty_pool_yields_nothing() {
+    let pool = ConcurrentPool::<u32>::new();
+    assert_eq!(pool.iter().len(), 0);
+    assert_eq!((&pool).into_iter().next(), None);
+    assert_eq!(pool.drain().next(), None);
+    assert_eq!(pool.into_iter().next(), None);
+}
+
+#[test]
+fn iter_and_borrowed_into_iter_leave_the_pool_alone() {
+    let pool: ConcurrentPool<u32> = (1..=4).collect();
+    assert_eq!(pool.iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+    let mut seen = Vec::new();
+    for value in &pool {
+        seen.push(value);
+    }
+    assert_eq!(seen, [1, 2, 3, 4]);
+    assert_eq!(pool.len(), 4);
+}
+
+#[test]
+fn iter_sees_a_prefix_of_concurrent_pushes() {
+    iterate_while_pushing(ConcurrentPool::new(), false);
+    iterate_while_pushing(ConcurrentPool::with_reclaim(Hazard), false);
+}
+
+#[test]
+fn iter_finishes_under_steady_pops() {
+    iterate_while_pushing(ConcurrentPool::new(), true);
+    iterate_while_pushing(ConcurrentPool::with_reclaim(Hazard), true);
+}
+
+#[test]
+fn drain_moves_owned_values_out_front_first() {
+    let pool: ConcurrentPool<String> = ["a", "b", "c"].map(String::from).into_iter().collect();
+    assert_eq!(pool.drain().next().as_deref(), Some("a"));
+    assert_eq!(pool.len(), 2);
+
+    pool.push("d".to_string()).unwrap();
+    assert_eq!(pool.drain().collect::<Vec<_>>(), ["b", "c", "d"]);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn owned_into_iter_pops_non_copy_values() {
+    let pool = ConcurrentPool::with_reclaim(Hazard);
+    for word in ["x", "y"] {
+        pool.push(Box::new(word.to_string())).unwrap();
+    }
+    let words: Vec<String> = pool.into_iter().map(|word| *word).collect();
+    assert_eq!(words, ["x", "y"]);
+}
@@ -0,0 +1,133 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p concurrent_pool --release --test loom`.
+use concurrency_core::{PopError, PushError};
+use concurrent_pool::{Blocking, BlockingConcurrentQueue, ConcurrentPool};
+use loom::sync::Arc;
+use loom::thread;
+
+#[test]
+fn concurrent_pushes_both_land() {
+    loom::model(|| {
+        let pool = Arc::new(ConcurrentPool::new());
+        let producers: Vec<_> = (1..=2)
+            .map(|value| {
+                let pool = Arc::clone(&pool);
+                thread::spawn(move || pool.push(value).unwrap())
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut values = vec![pool.try_pop().unwrap(), pool.try_pop().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(pool.try_pop(), None);
+    });
+}
+
+#[test]
+fn pop_racing_push_keeps_fifo_order() {
+    loom::model(|| {
+        let pool = Arc::new(ConcurrentPool::new());
+        let producer = {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || {
+                pool.push(1).unwrap();
+                pool.push(2).unwrap();
+            })
+        };
+
+        let mut values: Vec<_> = pool.try_pop().into_iter().collect();
+        producer.join().unwrap();
+        while let Some(value) = pool.try_pop() {
+            values.push(value);
+        }
+        assert_eq!(values, [1, 2]);
+    });
+}
+
+#[test]
+fn racing_pushes_respect_max_size() {
+    loom::model(|| {
+        let pool = Arc::new(ConcurrentPool::bounded(1));
+        let producer = {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || pool.push(1).is_ok())
+        };
+
+        let pushed = pool.push(2).is_ok();
+        assert!(pushed != producer.join().unwrap());
+        assert_eq!(pool.len(), 1);
+    });
+}
+
+#[test]
+fn peek_racing_pop_sees_a_whole_item() {
+    loom::model(|| {
+        let pool = Arc::new(ConcurrentPool::new());
+        pool.push(1).unwrap();
+        pool.push(2).unwrap();
+        let consumer = {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || pool.try_pop())
+        };
+
+        assert!(matches!(pool.peek(), Some(1) | Some(2)));
+        assert_eq!(consumer.join().unwrap(), Some(1));
+    });
+}
+
+#[test]
+fn blocking_pop_is_woken_by_push() {
+    loom::model(|| {
+        let pool = Arc::new(Blocking::new(ConcurrentPool::new()));
+        let consumer = {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || pool.pop())
+        };
+
+        pool.push(1).unwrap();
+        assert_eq!(consumer.join().unwrap(), Ok(1));
+    });
+}
+
+#[test]
+fn blocking_push_into_a_full_pool_waits_for_a_pop() {
+    loom::model(|| {
+        let pool = Arc::new(Blocking::new(ConcurrentPool::bounded(1)));
+        pool.push(1).unwrap();
+        let producer = {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || pool.push(2))
+        };
+
+        assert_eq!(pool.pop(), Ok(1));
+        assert_eq!(producer.join().unwrap(), Ok(()));
+        assert_eq!(pool.pop(), Ok(2));
+    });
+}
+
+#[test]
+fn close_wakes_blocked_pushes_and_pops() {
+    loom::model(|| {
+        let full = Arc::new(Blocking::new(ConcurrentPool::bounded(1)));
+        let empty = Arc::new(Blocking::new(ConcurrentPool::<u32>::new()));
+        full.push(1).unwrap();
+        let producer = {
+            let full = Arc::clone(&full);
+            thread::spawn(move || full.push(2))
+        };
+        let consumer = {
+            let empty = Arc::clone(&empty);
+            thread::spawn(move || empty.pop())
+        };
+
+        full.close();
+        empty.close();
+        assert_eq!(producer.join().unwrap(), Err(PushError::Closed(2)));
+        assert_eq!(consumer.join().unwrap(), Err(PopError::Closed));
+        assert_eq!(full.pop(), Ok(1));
+    });
+}
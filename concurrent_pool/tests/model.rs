@@ -0,0 +1,104 @@
+use concurrent_pool::{ConcurrentPool, Hazard, Spin};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::collections::VecDeque;
+
+// Runs random single-threaded operation sequences against the pool and a
+// `VecDeque`, checking every result. proptest shrinks a failing sequence
+// down to the shortest one that still fails.
+#[derive(Debug, Clone)]
+enum Op {
+    Push(u8),
+    TryPop,
+    PushRange(Vec<u8>),
+    PopRange(usize),
+    Peek,
+    Iter,
+    Drain,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u8>().prop_map(Op::Push),
+        2 => Just(Op::TryPop),
+        1 => vec(any::<u8>(), 0..8).prop_map(Op::PushRange),
+        1 => (0..8usize).prop_map(Op::PopRange),
+        1 => Just(Op::Peek),
+        1 => Just(Op::Iter),
+        1 => Just(Op::Drain),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn concurrent_pool_matches_vecdeque(
+        max_size in proptest::option::of(0..8usize),
+        ops in vec(op(), 0..64),
+    ) {
+        let pool = match max_size {
+            Some(max_size) => ConcurrentPool::bounded(max_size),
+            None => ConcurrentPool::new(),
+        };
+        let max_size = max_size.unwrap_or(usize::MAX);
+        let mut model = VecDeque::new();
+        for op in ops {
+            match op {
+                Op::Push(value) => {
+                    let expected = if model.len() < max_size {
+                        model.push_back(value);
+                        Ok(())
+                    } else {
+                        Err(value)
+                    };
+                    prop_assert_eq!(pool.push(value), expected);
+                }
+                Op::TryPop => prop_assert_eq!(pool.try_pop(), model.pop_front()),
+                Op::PushRange(items) => {
+                    let fits = items.len().min(max_size - model.len());
+                    model.extend(&items[..fits]);
+                    prop_assert_eq!(pool.push_range(items), fits);
+                }
+                Op::PopRange(count) => {
+                    let expected: Vec<_> = model.drain(..count.min(model.len())).collect();
+                    prop_assert_eq!(pool.pop_range(count), expected);
+                }
+                Op::Peek => prop_assert_eq!(pool.peek(), model.front().copied()),
+                Op::Iter => {
+                    let items: Vec<_> = pool.iter().collect();
+                    prop_assert_eq!(items, Vec::from(model.clone()));
+                }
+                Op::Drain => {
+                    let expected: Vec<_> = model.drain(..).collect();
+                    prop_assert_eq!(pool.drain().collect::<Vec<_>>(), expected);
+                }
+            }
+            prop_assert_eq!(pool.len(), model.len());
+            prop_assert_eq!(pool.is_empty(), model.is_empty());
+        }
+    }
+
+    #[test]
+    fn with_backoff_keeps_items_and_bound(items in vec(any::<u8>(), 0..32), bound in 0..16usize) {
+        let pool = ConcurrentPool::bounded(bound);
+        pool.push_range(items.iter().copied());
+        let pool = pool.with_backoff(Spin::default());
+        prop_assert_eq!(pool.max_size(), bound);
+        prop_assert_eq!(pool.iter().collect::<Vec<_>>(), &items[..items.len().min(bound)]);
+    }
+
+    #[test]
+    fn hazard_pool_keeps_bound_and_factory(items in vec(any::<u8>(), 0..32), bound in 0..16usize) {
+        let pool = ConcurrentPool::with_reclaim(Hazard)
+            .with_max_size(bound)
+            .with_object_factory(|| u8::MAX);
+        for &item in &items {
+            let _ = pool.push(item);
+        }
+        prop_assert_eq!(pool.max_size(), bound);
+        prop_assert_eq!(pool.len(), items.len().min(bound));
+        for &item in items.iter().take(bound) {
+            prop_assert_eq!(pool.get().detach(), item);
+        }
+        prop_assert_eq!(pool.get().detach(), u8::MAX);
+    }
+}
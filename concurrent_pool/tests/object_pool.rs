@@ -0,0 +1,147 @@
+use concurrent_pool::{ConcurrentPool, Hazard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const THREADS: usize = 4;
+
+// Counts how many objects a factory made and how many of them were dropped.
+#[derive(Default)]
+struct Counts {
+    made: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+struct Object {
+    uses: usize,
+    counts: Arc<Counts>,
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        self.counts.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn object_pool(max: usize) -> (ConcurrentPool<Object>, Arc<Counts>) {
+    let counts = Arc::new(Counts::default());
+    let factory = {
+        let counts = Arc::clone(&counts);
+        move || {
+            counts.made.fetch_add(1, Ordering::Relaxed);
+            Object {
+                uses: 0,
+                counts: Arc::clone(&counts),
+            }
+        }
+    };
+
+    (ConcurrentPool::with_factory(max, factory), counts)
+}
+
+#[test]
+fn get_reuses_idle_objects_before_making_new_ones() {
+    let (pool, counts) = object_pool(2);
+    let mut first = pool.get();
+    first.uses += 1;
+    drop(first);
+    assert_eq!(pool.len(), 1);
+
+    let mut again = pool.get();
+    assert_eq!(again.uses, 1);
+    again.uses += 1;
+    let other = pool.get();
+    assert_eq!(other.uses, 0);
+    assert_eq!(counts.made.load(Ordering::Relaxed), 2);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn reset_runs_before_an_object_goes_back() {
+    let pool = ConcurrentPool::with_factory(1, Vec::new).with_reset(Vec::clear);
+    pool.get().extend_from_slice(b"scratch");
+
+    let buffer = pool.try_get().unwrap();
+    assert!(buffer.is_empty());
+    assert!(buffer.capacity() >= 7);
+}
+
+#[test]
+fn objects_returned_beyond_max_are_dropped() {
+    let (pool, counts) = object_pool(1);
+    let borrowed: Vec<_> = (0..3).map(|_| pool.get()).collect();
+    drop(borrowed);
+
+    assert_eq!(pool.len(), 1);
+    assert_eq!(counts.made.load(Ordering::Relaxed), 3);
+    assert_eq!(counts.dropped.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn detach_takes_the_object_out_for_good() {
+    let pool = ConcurrentPool::with_factory(2, String::new).with_reset(String::clear);
+    let mut kept = pool.get();
+    kept.push_str("kept");
+
+    let kept = kept.detach();
+    assert_eq!(kept, "kept");
+    assert!(pool.is_empty());
+    assert!(pool.try_get().is_none());
+}
+
+#[test]
+fn try_get_never_makes_an_object() {
+    let (pool, counts) = object_pool(2);
+    assert!(pool.try_get().is_none());
+
+    let idle = Object {
+        uses: 7,
+        counts: Arc::clone(&counts),
+    };
+    assert!(pool.push(idle).is_ok());
+    assert_eq!(pool.try_get().map(|object| object.uses), Some(7));
+    assert_eq!(counts.made.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+#[should_panic(expected = "without a factory")]
+fn get_without_a_factory_panics_on_an_empty_pool() {
+    let pool = ConcurrentPool::<String>::new();
+    let _ = pool.get();
+}
+
+#[test]
+fn threads_share_objects_without_making_more_than_they_hold() {
+    let (pool, counts) = object_pool(THREADS);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..1_000 {
+                    pool.get().uses += 1;
+                }
+            });
+        }
+    });
+
+    // Every thread holds at most one object at a time, so no more than
+    // `THREADS` ever exist and none are dropped.
+    let made = counts.made.load(Ordering::Relaxed);
+    assert!((1..=THREADS).contains(&made), "made {made} objects");
+    assert_eq!(counts.dropped.load(Ordering::Relaxed), 0);
+    assert_eq!(pool.len(), made);
+    let uses: usize = pool.drain().map(|object| object.uses).sum();
+    assert_eq!(uses, THREADS * 1_000);
+}
+
+#[test]
+fn hazard_object_pool_reuses_objects() {
+    let pool = ConcurrentPool::with_reclaim(Hazard)
+        .with_max_size(1)
+        .with_object_factory(|| vec![0u8; 16])
+        .with_reset(|buffer: &mut Vec<u8>| buffer.fill(0));
+    pool.get()[0] = 1;
+
+    let buffer = pool.get();
+    assert_eq!(buffer.len(), 16);
+    assert!(buffer.iter().all(|&byte| byte == 0));
+}
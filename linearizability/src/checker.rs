@@ -0,0 +1,253 @@
+use crate::{History, Operation};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// The sequential behaviour a concurrent history is checked against.
+pub trait Specification {
+    type Op: fmt::Debug;
+    type Ret: fmt::Debug + PartialEq;
+    type State: Clone + Eq + Hash;
+    type Key: Eq + Hash;
+
+    fn init(&self) -> Self::State;
+
+    /// Applies `op` to `state` and returns what a sequential run would.
+    fn step(&self, state: &mut Self::State, op: &Self::Op) -> Self::Ret;
+
+    /// Operations sharing a key are kept or dropped together while a
+    /// violation is minimised, e.g. a push and the pop that returned its
+    /// value. Without that, dropping the push alone would leave a pop of a
+    /// value that never existed, which is a violation but not the one that
+    /// was found. `None` lets the operation be dropped on its own.
+    fn key(&self, op: &Self::Op, ret: &Self::Ret) -> Option<Self::Key>;
+}
+
+/// Checks that `history` is linearizable with respect to `spec`, i.e. that
+/// every operation can be given a point between its invocation and response
+/// so that running them in that order against `spec` gives the recorded
+/// results.
+///
+/// Uses the Wing–Gong search with Lowe's memoisation of (linearized set,
+/// state) pairs. On failure the history is shrunk to a minimal subset that
+/// is still not linearizable: dropping any further operation, or group of
+/// operations sharing a [`Specification::key`], makes it linearizable.
+pub fn check<S: Specification>(
+    spec: &S,
+    history: &History<S::Op, S::Ret>,
+) -> Result<(), Violation<S::Op, S::Ret>>
+where
+    S::Op: Clone,
+    S::Ret: Clone,
+{
+    let operations = history.operations();
+    if is_linearizable(spec, operations) {
+        return Ok(());
+    }
+
+    let history = minimize(spec, operations.to_vec());
+    Err(Violation { history })
+}
+
+/// A history that no sequential order explains. See [`check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<O, R> {
+    history: Vec<Operation<O, R>>,
+}
+
+impl<O, R> Violation<O, R> {
+    /// The minimal non-linearizable history, ordered by invocation time.
+    pub fn history(&self) -> &[Operation<O, R>] {
+        &self.history
+    }
+}
+
+impl<O: fmt::Debug, R: fmt::Debug> fmt::Display for Violation<O, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "history is not linearizable:")?;
+        for op in &self.history {
+            write!(
+                f,
+                "\n  [{:>6}, {:>6}] thread {}: {:?} -> {:?}",
+                op.invoke, op.response, op.thread, op.op, op.ret
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<O: fmt::Debug, R: fmt::Debug> Error for Violation<O, R> {}
+
+const NIL: usize = usize::MAX;
+
+// The events of a history as a doubly linked list in time order, with the
+// head sentinel at index 0. Linearizing an operation unlinks its call and
+// return; backtracking links them back in the reverse order. Call nodes
+// point at their return node through `ret`.
+struct Node {
+    op: usize,
+    ret: Option<usize>,
+    prev: usize,
+    next: usize,
+}
+
+fn is_linearizable<S: Specification>(spec: &S, operations: &[Operation<S::Op, S::Ret>]) -> bool {
+    let mut events: Vec<_> = operations
+        .iter()
+        .enumerate()
+        .flat_map(|(i, op)| [(op.invoke, false, i), (op.response, true, i)])
+        .collect();
+    // On a tie the call goes first, treating the two operations as
+    // overlapping.
+    events.sort_by_key(|&(time, is_return, _)| (time, is_return));
+
+    let mut nodes = vec![Node {
+        op: NIL,
+        ret: None,
+        prev: NIL,
+        next: NIL,
+    }];
+    let mut call_node = vec![NIL; operations.len()];
+    for &(_, is_return, op) in &events {
+        let index = nodes.len();
+        if is_return {
+            nodes[call_node[op]].ret = Some(index);
+        } else {
+            call_node[op] = index;
+        }
+        nodes[index - 1].next = index;
+        nodes.push(Node {
+            op,
+            ret: None,
+            prev: index - 1,
+            next: NIL,
+        });
+    }
+
+    let mut linearized = vec![0u64; operations.len().div_ceil(64)];
+    let mut seen = HashSet::new();
+    let mut calls = Vec::new();
+    let mut state = spec.init();
+    let mut entry = nodes[0].next;
+    while nodes[0].next != NIL {
+        let Node { op: index, ret, .. } = nodes[entry];
+        match ret {
+            Some(ret) => {
+                let op = &operations[index];
+                let mut next_state = state.clone();
+                let mut linearized_here = false;
+                if spec.step(&mut next_state, &op.op) == op.ret {
+                    linearized[index / 64] |= 1 << (index % 64);
+                    if seen.insert((linearized.clone(), next_state.clone())) {
+                        calls.push((entry, state));
+                        state = next_state;
+                        unlink(&mut nodes, entry, ret);
+                        entry = nodes[0].next;
+                        linearized_here = true;
+                    } else {
+                        linearized[index / 64] &= !(1 << (index % 64));
+                    }
+                }
+                if !linearized_here {
+                    entry = nodes[entry].next;
+                }
+            }
+            None => {
+                // Some operation returned before any pending call could be
+                // linearized, so undo the most recent choice.
+                let Some((call, previous)) = calls.pop() else {
+                    return false;
+                };
+                state = previous;
+                let Node { op: index, ret, .. } = nodes[call];
+                linearized[index / 64] &= !(1 << (index % 64));
+                relink(&mut nodes, call, ret.unwrap());
+                entry = nodes[call].next;
+            }
+        }
+    }
+
+    true
+}
+
+fn unlink(nodes: &mut [Node], call: usize, ret: usize) {
+    for index in [call, ret] {
+        let Node { prev, next, .. } = nodes[index];
+        nodes[prev].next = next;
+        if next != NIL {
+            nodes[next].prev = prev;
+        }
+    }
+}
+
+fn relink(nodes: &mut [Node], call: usize, ret: usize) {
+    for index in [ret, call] {
+        let Node { prev, next, .. } = nodes[index];
+        nodes[prev].next = index;
+        if next != NIL {
+            nodes[next].prev = index;
+        }
+    }
+}
+
+// Delta debugging over groups of operations: try dropping ever smaller runs
+// of groups, keeping every drop that leaves the history non-linearizable.
+// Dropping a group can make one that was kept earlier droppable again, so
+// runs of one group are retried until a whole pass drops nothing. After
+// that, no single group can be dropped.
+fn minimize<S: Specification>(
+    spec: &S,
+    operations: Vec<Operation<S::Op, S::Ret>>,
+) -> Vec<Operation<S::Op, S::Ret>>
+where
+    S::Op: Clone,
+    S::Ret: Clone,
+{
+    let mut groups: Vec<Vec<Operation<S::Op, S::Ret>>> = Vec::new();
+    let mut group_of_key = HashMap::new();
+    for op in operations {
+        match spec.key(&op.op, &op.ret) {
+            Some(key) => {
+                let group = *group_of_key.entry(key).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[group].push(op);
+            }
+            None => groups.push(vec![op]),
+        }
+    }
+
+    let flatten = |groups: &[Vec<Operation<S::Op, S::Ret>>]| {
+        let mut operations: Vec<_> = groups.iter().flatten().cloned().collect();
+        operations.sort_by_key(|op| op.invoke);
+        operations
+    };
+
+    let mut run = groups.len().div_ceil(2);
+    loop {
+        let mut dropped = false;
+        let mut start = 0;
+        while start < groups.len() {
+            let end = (start + run).min(groups.len());
+            let mut candidate = groups.clone();
+            candidate.drain(start..end);
+            if !is_linearizable(spec, &flatten(&candidate)) {
+                groups = candidate;
+                dropped = true;
+            } else {
+                start = end;
+            }
+        }
+
+        if run > 1 {
+            run /= 2;
+        } else if !dropped {
+            break;
+        }
+    }
+
+    flatten(&groups)
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// One completed operation: what was called, what it returned, and the
+/// logical times at which it was invoked and returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation<O, R> {
+    pub thread: usize,
+    pub op: O,
+    pub ret: R,
+    pub invoke: u64,
+    pub response: u64,
+}
+
+/// A set of completed operations, each with the interval it was in flight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<O, R> {
+    operations: Vec<Operation<O, R>>,
+}
+
+impl<O, R> History<O, R> {
+    /// Builds a history by hand. Every operation's `invoke` must come before
+    /// its `response`; timestamps should be distinct across operations.
+    pub fn from_operations(mut operations: Vec<Operation<O, R>>) -> Self {
+        assert!(
+            operations.iter().all(|op| op.invoke < op.response),
+            "an operation must be invoked before it returns"
+        );
+        operations.sort_by_key(|op| op.invoke);
+
+        Self { operations }
+    }
+
+    /// The operations, ordered by invocation time.
+    pub fn operations(&self) -> &[Operation<O, R>] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+/// Records the operations several threads run against a shared structure.
+///
+/// Invocations and responses are stamped from one shared `SeqCst` counter,
+/// so if an operation returned before another was invoked, its `response` is
+/// lower than the other's `invoke`. Each thread records into its own
+/// [`ThreadLog`] and only touches the shared list once, when the log is
+/// dropped, so recording adds as little synchronisation as possible to the
+/// code under test.
+#[derive(Debug)]
+pub struct Recorder<O, R> {
+    clock: AtomicU64,
+    threads: AtomicUsize,
+    operations: Mutex<Vec<Operation<O, R>>>,
+}
+
+impl<O, R> Recorder<O, R> {
+    pub fn new() -> Self {
+        Self {
+            clock: AtomicU64::new(0),
+            threads: AtomicUsize::new(0),
+            operations: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Starts a log for the calling thread.
+    pub fn thread(&self) -> ThreadLog<'_, O, R> {
+        ThreadLog {
+            recorder: self,
+            thread: self.threads.fetch_add(1, Ordering::Relaxed),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns everything recorded by logs that have been dropped.
+    pub fn into_history(self) -> History<O, R> {
+        History::from_operations(self.operations.into_inner().unwrap())
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+impl<O, R> Default for Recorder<O, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single thread's view of a [`Recorder`].
+#[derive(Debug)]
+pub struct ThreadLog<'a, O, R> {
+    recorder: &'a Recorder<O, R>,
+    thread: usize,
+    operations: Vec<Operation<O, R>>,
+}
+
+impl<O, R: Clone> ThreadLog<'_, O, R> {
+    /// Runs `call`, which should perform `op` on the structure under test,
+    /// and records it along with its result.
+    pub fn record(&mut self, op: O, call: impl FnOnce() -> R) -> R {
+        let invoke = self.recorder.tick();
+        let ret = call();
+        let response = self.recorder.tick();
+        self.operations.push(Operation {
+            thread: self.thread,
+            op,
+            ret: ret.clone(),
+            invoke,
+            response,
+        });
+
+        ret
+    }
+}
+
+impl<O, R> Drop for ThreadLog<'_, O, R> {
+    fn drop(&mut self) {
+        self.recorder
+            .operations
+            .lock()
+            .unwrap()
+            .append(&mut self.operations);
+    }
+}
@@ -0,0 +1,7 @@
+mod checker;
+mod history;
+mod spec;
+
+pub use checker::{check, Specification, Violation};
+pub use history::{History, Operation, Recorder, ThreadLog};
+pub use spec::{Op, QueueSpec, Ret, StackSpec};
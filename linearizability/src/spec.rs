@@ -0,0 +1,119 @@
+use crate::Specification;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// An operation on a queue or stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op<T> {
+    Push(T),
+    Pop,
+}
+
+/// The result of an [`Op`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ret<T> {
+    Pushed,
+    Popped(Option<T>),
+}
+
+/// A FIFO queue. Pushed values should be distinct so a violation can be
+/// minimised by dropping a value's push and pop together.
+pub struct QueueSpec<T>(PhantomData<fn() -> T>);
+
+impl<T> QueueSpec<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for QueueSpec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for QueueSpec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QueueSpec")
+    }
+}
+
+impl<T: Clone + Eq + Hash + fmt::Debug> Specification for QueueSpec<T> {
+    type Op = Op<T>;
+    type Ret = Ret<T>;
+    type State = VecDeque<T>;
+    type Key = T;
+
+    fn init(&self) -> Self::State {
+        VecDeque::new()
+    }
+
+    fn step(&self, state: &mut Self::State, op: &Self::Op) -> Self::Ret {
+        match op {
+            Op::Push(value) => {
+                state.push_back(value.clone());
+                Ret::Pushed
+            }
+            Op::Pop => Ret::Popped(state.pop_front()),
+        }
+    }
+
+    fn key(&self, op: &Self::Op, ret: &Self::Ret) -> Option<Self::Key> {
+        match (op, ret) {
+            (Op::Push(value), _) | (_, Ret::Popped(Some(value))) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// A LIFO stack. Pushed values should be distinct so a violation can be
+/// minimised by dropping a value's push and pop together.
+pub struct StackSpec<T>(PhantomData<fn() -> T>);
+
+impl<T> StackSpec<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for StackSpec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for StackSpec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StackSpec")
+    }
+}
+
+impl<T: Clone + Eq + Hash + fmt::Debug> Specification for StackSpec<T> {
+    type Op = Op<T>;
+    type Ret = Ret<T>;
+    type State = Vec<T>;
+    type Key = T;
+
+    fn init(&self) -> Self::State {
+        Vec::new()
+    }
+
+    fn step(&self, state: &mut Self::State, op: &Self::Op) -> Self::Ret {
+        match op {
+            Op::Push(value) => {
+                state.push(value.clone());
+                Ret::Pushed
+            }
+            Op::Pop => Ret::Popped(state.pop()),
+        }
+    }
+
+    fn key(&self, op: &Self::Op, ret: &Self::Ret) -> Option<Self::Key> {
+        match (op, ret) {
+            (Op::Push(value), _) | (_, Ret::Popped(Some(value))) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
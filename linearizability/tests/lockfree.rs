@@ -0,0 +1,232 @@
+use concurrent_pool::{ConcurrentPool, NoBackoff};
+use linearizability::{
+    check, History, Op, Operation, QueueSpec, Recorder, Ret, Specification, StackSpec,
+};
+use nonblocking_queue::{ConcurrentQueue, Hazard, LockFreeQueue, Spin};
+use nonblocking_stack::{ConcurrentStack, Exponential, LockFreeStack};
+use ring_queue::RingQueue;
+use std::thread;
+
+const THREADS: u32 = 4;
+const OPS_PER_THREAD: u32 = 200;
+
+// Every thread alternates short bursts of pushes and pops of values unique
+// to it, so the structure keeps going between empty and non-empty.
+fn run<S: Sync>(
+    structure: &S,
+    push: impl Fn(&S, u32) + Sync,
+    pop: impl Fn(&S) -> Option<u32> + Sync,
+) -> History<Op<u32>, Ret<u32>> {
+    let recorder = Recorder::new();
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let (recorder, push, pop) = (&recorder, &push, &pop);
+            scope.spawn(move || {
+                let mut log = recorder.thread();
+                for i in 0..OPS_PER_THREAD {
+                    if i % 4 < 2 {
+                        let value = thread * OPS_PER_THREAD + i;
+                        log.record(Op::Push(value), || {
+                            push(structure, value);
+                            Ret::Pushed
+                        });
+                    } else {
+                        log.record(Op::Pop, || Ret::Popped(pop(structure)));
+                    }
+                }
+            });
+        }
+    });
+
+    recorder.into_history()
+}
+
+fn assert_linearizable<S>(history: &History<Op<u32>, Ret<u32>>, spec: &S)
+where
+    S: Specification<Op = Op<u32>, Ret = Ret<u32>>,
+{
+    if let Err(violation) = check(spec, history) {
+        panic!("{violation}");
+    }
+}
+
+fn push_unbounded<Q: ConcurrentQueue<u32>>(queue: &Q, value: u32) {
+    queue.try_push(value).unwrap();
+}
+
+// Runs a queue, a stack and a pool built the same way, e.g. with the same
+// reclaimer, through the checker.
+fn check_all_structures<Q, S, P>(
+    make_queue: impl Fn() -> Q,
+    make_stack: impl Fn() -> S,
+    make_pool: impl Fn() -> P,
+) where
+    Q: ConcurrentQueue<u32> + Sync,
+    S: ConcurrentStack<u32> + Sync,
+    P: ConcurrentQueue<u32> + Sync,
+{
+    for _ in 0..10 {
+        let history = run(&make_queue(), push_unbounded, Q::try_pop);
+        assert_linearizable(&history, &QueueSpec::new());
+
+        let history = run(
+            &make_stack(),
+            |stack, value| stack.try_push(value).unwrap(),
+            S::try_pop,
+        );
+        assert_linearizable(&history, &StackSpec::new());
+
+        let history = run(&make_pool(), push_unbounded, P::try_pop);
+        assert_linearizable(&history, &QueueSpec::new());
+    }
+}
+
+#[test]
+fn lockfree_queue_is_linearizable() {
+    for _ in 0..10 {
+        let queue = LockFreeQueue::new();
+        let history = run(&queue, LockFreeQueue::enqueue, LockFreeQueue::dequeue);
+        assert_linearizable(&history, &QueueSpec::new());
+    }
+}
+
+#[test]
+fn lockfree_stack_is_linearizable() {
+    for _ in 0..10 {
+        let stack = LockFreeStack::new();
+        let history = run(&stack, LockFreeStack::push, LockFreeStack::try_pop);
+        assert_linearizable(&history, &StackSpec::new());
+    }
+}
+
+#[test]
+fn elimination_stack_is_linearizable() {
+    for _ in 0..10 {
+        let stack = LockFreeStack::new().with_elimination(2);
+        let history = run(&stack, LockFreeStack::push, LockFreeStack::try_pop);
+        assert_linearizable(&history, &StackSpec::new());
+    }
+}
+
+#[test]
+fn concurrent_pool_is_linearizable() {
+    for _ in 0..10 {
+        let pool = ConcurrentPool::new();
+        let history = run(
+            &pool,
+            |pool, value| pool.push(value).unwrap(),
+            ConcurrentPool::try_pop,
+        );
+        assert_linearizable(&history, &QueueSpec::new());
+    }
+}
+
+#[test]
+fn hazard_reclamation_keeps_structures_linearizable() {
+    check_all_structures(
+        || LockFreeQueue::with_reclaim(Hazard),
+        || LockFreeStack::with_reclaim(Hazard),
+        || ConcurrentPool::with_reclaim(Hazard),
+    );
+}
+
+#[test]
+fn ring_queue_is_linearizable() {
+    // `try_pop` can come back empty while an earlier push is still writing
+    // and `try_push` can come back full while a pop is still reading, which
+    // is weaker than the queue spec allows. Both are retried, so this checks
+    // the order items come out in. Every thread has at most two items in
+    // the queue, so retries always end, and the ring wraps many times.
+    for _ in 0..10 {
+        let queue = RingQueue::new(2 * THREADS as usize);
+        let history = run(
+            &queue,
+            |queue, mut value| {
+                while let Err(rejected) = queue.try_push(value) {
+                    value = rejected;
+                    thread::yield_now();
+                }
+            },
+            |queue| loop {
+                if let Some(value) = queue.try_pop() {
+                    return Some(value);
+                }
+                thread::yield_now();
+            },
+        );
+        assert_linearizable(&history, &QueueSpec::new());
+    }
+}
+
+#[test]
+fn backoff_strategies_keep_structures_linearizable() {
+    check_all_structures(
+        || LockFreeQueue::new().with_backoff(Spin::default()),
+        || LockFreeStack::new().with_backoff(Exponential::default()),
+        || ConcurrentPool::new().with_backoff(NoBackoff),
+    );
+}
+
+fn operation(
+    thread: usize,
+    op: Op<u32>,
+    ret: Ret<u32>,
+    invoke: u64,
+    response: u64,
+) -> Operation<Op<u32>, Ret<u32>> {
+    Operation {
+        thread,
+        op,
+        ret,
+        invoke,
+        response,
+    }
+}
+
+#[test]
+fn overlapping_operations_may_linearize_in_either_order() {
+    // The pop overlaps both pushes, so it may take either value.
+    let history = History::from_operations(vec![
+        operation(0, Op::Push(1), Ret::Pushed, 0, 3),
+        operation(1, Op::Push(2), Ret::Pushed, 1, 4),
+        operation(2, Op::Pop, Ret::Popped(Some(2)), 2, 5),
+    ]);
+
+    assert!(check(&QueueSpec::new(), &history).is_ok());
+    assert!(check(&StackSpec::new(), &history).is_ok());
+}
+
+#[test]
+fn violation_is_shrunk_to_a_minimal_history() {
+    // 1 is pushed before 2, so a FIFO pop must see 1 first. Values 3 and 4
+    // and the empty pop are noise the report should leave out.
+    let history = History::from_operations(vec![
+        operation(0, Op::Push(3), Ret::Pushed, 0, 1),
+        operation(1, Op::Pop, Ret::Popped(Some(3)), 2, 3),
+        operation(0, Op::Push(1), Ret::Pushed, 4, 6),
+        operation(1, Op::Push(4), Ret::Pushed, 5, 9),
+        operation(0, Op::Push(2), Ret::Pushed, 7, 8),
+        operation(0, Op::Pop, Ret::Popped(Some(4)), 10, 13),
+        operation(1, Op::Pop, Ret::Popped(Some(2)), 11, 12),
+        operation(1, Op::Pop, Ret::Popped(Some(1)), 14, 15),
+        operation(0, Op::Pop, Ret::Popped(None), 16, 17),
+    ]);
+
+    let violation = check(&QueueSpec::new(), &history).unwrap_err();
+    let ops: Vec<_> = violation
+        .history()
+        .iter()
+        .map(|op| (op.op, op.ret))
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            (Op::Push(1), Ret::Pushed),
+            (Op::Push(2), Ret::Pushed),
+            (Op::Pop, Ret::Popped(Some(2))),
+            (Op::Pop, Ret::Popped(Some(1))),
+        ]
+    );
+    // Popping 2, 4, 1 is exactly what a stack would do.
+    assert!(check(&StackSpec::new(), &history).is_ok());
+}
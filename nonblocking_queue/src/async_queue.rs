@@ -0,0 +1,226 @@
+use crate::LockFreeQueue;
+use concurrency_core::sync::atomic::{AtomicBool, Ordering};
+use concurrency_core::{ConcurrentQueue, Epoch, PopError, PushError, Reclaim, WakerSet};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A [`LockFreeQueue`] whose consumers can `.await` items.
+///
+/// `push` and `try_pop` stay lock-free; a push only takes the waker list's
+/// lock when a task is actually waiting. The queue is unbounded, so pushing
+/// never has to wait.
+pub struct AsyncQueue<T, R: Reclaim = Epoch> {
+    queue: LockFreeQueue<T, R>,
+    wakers: WakerSet,
+    closed: AtomicBool,
+}
+
+impl<T> AsyncQueue<T> {
+    pub fn new() -> Self {
+        Self::with_reclaim(Epoch)
+    }
+}
+
+impl<T, R: Reclaim> AsyncQueue<T, R> {
+    pub fn with_reclaim(reclaim: R) -> Self {
+        Self {
+            queue: LockFreeQueue::with_reclaim(reclaim),
+            wakers: WakerSet::new(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn push(&self, value: T) -> Result<(), PushError<T>> {
+        if self.is_closed() {
+            return Err(PushError::Closed(value));
+        }
+        self.queue.enqueue(value);
+        self.wakers.notify_one();
+
+        Ok(())
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.queue.dequeue()
+    }
+
+    /// Waits for an item. The returned future can be dropped at any point
+    /// without losing an item or a wakeup.
+    pub fn pop(&self) -> Pop<'_, T, R> {
+        Pop {
+            queue: self,
+            key: None,
+        }
+    }
+
+    /// Closes the queue and wakes every waiting `pop`.
+    ///
+    /// Pushes fail from then on, while pops keep draining whatever is left.
+    /// A push that was already past its check when `close` ran may still
+    /// land.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.wakers.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Turns a shared queue into a `Stream` of popped items that ends once
+    /// the queue is closed and drained.
+    pub fn into_stream(self: Arc<Self>) -> PopStream<T, R> {
+        PopStream {
+            queue: self,
+            key: None,
+        }
+    }
+
+    /// Turns a shared queue into a `Sink`. Closing the sink closes the queue.
+    pub fn into_sink(self: Arc<Self>) -> PushSink<T, R> {
+        PushSink { queue: self }
+    }
+
+    fn poll_pop(&self, cx: &mut Context<'_>, key: &mut Option<usize>) -> Poll<Result<T, PopError>> {
+        let mut registered = false;
+        loop {
+            // Closing is final, so if it was seen before an empty pop there
+            // is nothing left to wait for.
+            let closed = self.is_closed();
+            if let Some(value) = self.queue.dequeue() {
+                self.wakers.remove(key);
+                return Poll::Ready(Ok(value));
+            }
+            if closed {
+                self.wakers.remove(key);
+                return Poll::Ready(Err(PopError::Closed));
+            }
+            if registered {
+                return Poll::Pending;
+            }
+
+            // Look again after registering: a push that landed in between
+            // either shows up now or sees the registration and wakes us.
+            self.wakers.register(key, cx.waker());
+            registered = true;
+        }
+    }
+}
+
+impl<T, R: Reclaim> ConcurrentQueue<T> for AsyncQueue<T, R> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.push(item).map_err(PushError::into_inner)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, R: Reclaim + Default> Default for AsyncQueue<T, R> {
+    fn default() -> Self {
+        Self::with_reclaim(R::default())
+    }
+}
+
+impl<T, R: Reclaim> fmt::Debug for AsyncQueue<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncQueue")
+            .field("queue", &self.queue)
+            .field("wakers", &self.wakers)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Future returned by [`AsyncQueue::pop`].
+pub struct Pop<'a, T, R: Reclaim = Epoch> {
+    queue: &'a AsyncQueue<T, R>,
+    key: Option<usize>,
+}
+
+impl<T, R: Reclaim> Future for Pop<'_, T, R> {
+    type Output = Result<T, PopError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.queue.poll_pop(cx, &mut this.key)
+    }
+}
+
+impl<T, R: Reclaim> Drop for Pop<'_, T, R> {
+    fn drop(&mut self) {
+        self.queue.wakers.cancel(&mut self.key);
+    }
+}
+
+/// `Stream` returned by [`AsyncQueue::into_stream`].
+pub struct PopStream<T, R: Reclaim = Epoch> {
+    queue: Arc<AsyncQueue<T, R>>,
+    key: Option<usize>,
+}
+
+impl<T, R: Reclaim> Stream for PopStream<T, R> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.queue
+            .poll_pop(cx, &mut this.key)
+            .map(|result| result.ok())
+    }
+}
+
+impl<T, R: Reclaim> Drop for PopStream<T, R> {
+    fn drop(&mut self) {
+        self.queue.wakers.cancel(&mut self.key);
+    }
+}
+
+/// `Sink` returned by [`AsyncQueue::into_sink`]. The queue is unbounded, so
+/// the sink is always ready.
+pub struct PushSink<T, R: Reclaim = Epoch> {
+    queue: Arc<AsyncQueue<T, R>>,
+}
+
+impl<T, R: Reclaim> Sink<T> for PushSink<T, R> {
+    type Error = PushError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        self.queue.push(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.queue.close();
+        Poll::Ready(Ok(()))
+    }
+}
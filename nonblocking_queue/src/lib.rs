@@ -0,0 +1,12 @@
+mod async_queue;
+mod lockfree_queue;
+mod spsc;
+
+pub use async_queue::{AsyncQueue, Pop, PopStream, PushSink};
+pub use concurrency_core::{
+    Backoff, Blocking, BlockingConcurrentQueue, ConcurrentQueue, Epoch, Exponential, Hazard,
+    LenMode, NoBackoff, PopError, PushError, Reclaim, Spin, SpinThenYield,
+};
+pub use crossbeam_epoch::{pin, Guard};
+pub use lockfree_queue::{Iter, LockFreeQueue};
+pub use spsc::{spsc, Consumer, Producer};
@@ -1,180 +1,321 @@
-use std::sync::atomic::{AtomicPtr, Ordering};
+use concurrency_core::sync::atomic::{AtomicPtr, Ordering};
+use concurrency_core::sync::cell::UnsafeCell;
+use concurrency_core::{
+    Backoff, ConcurrentQueue, Epoch, Hazard, LenCounter, LenMode, Reclaim, SpinThenYield,
+};
+use crossbeam_epoch::Guard;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
 
-#[derive(Debug)]
-pub struct LockFreeQueue<T> {
+pub struct LockFreeQueue<T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
     head: AtomicPtr<Node<T>>,
     tail: AtomicPtr<Node<T>>,
+    len: LenCounter,
+    backoff: B,
+    _reclaim: PhantomData<R>,
 }
 
-#[derive(Debug)]
+// `value` is initialised for every node reachable from `head.next`. The node
+// `head` points at is the sentinel, whose value has either never been written
+// or has already been moved out by the `dequeue` that made it the sentinel.
 struct Node<T> {
     next: AtomicPtr<Node<T>>,
-    value: Option<T>,
+    value: UnsafeCell<MaybeUninit<T>>,
 }
 
 impl<T> LockFreeQueue<T> {
     pub fn new() -> Self {
+        Self::with_reclaim(Epoch)
+    }
+}
+
+impl<T, R: Reclaim> LockFreeQueue<T, R> {
+    /// Creates a queue that frees dequeued nodes through `R`, e.g.
+    /// `LockFreeQueue::with_reclaim(Hazard)`.
+    pub fn with_reclaim(_reclaim: R) -> Self {
+        Self::with_parts(SpinThenYield::default())
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> LockFreeQueue<T, R, B> {
+    fn with_parts(backoff: B) -> Self {
         let sentinel_node = Box::into_raw(Box::new(Node {
-            value: None,
-            next: AtomicPtr::new(std::ptr::null_mut()),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            next: AtomicPtr::new(ptr::null_mut()),
         }));
 
         Self {
             head: AtomicPtr::new(sentinel_node),
             tail: AtomicPtr::new(sentinel_node),
+            len: LenCounter::default(),
+            backoff,
+            _reclaim: PhantomData,
+        }
+    }
+
+    /// Switches to another [`Backoff`] strategy for retrying after a lost
+    /// race, e.g. `LockFreeQueue::new().with_backoff(Spin::new(4))`. Items
+    /// already in the queue move over in order.
+    pub fn with_backoff<B2: Backoff>(self, backoff: B2) -> LockFreeQueue<T, R, B2> {
+        let mut queue = LockFreeQueue::with_parts(backoff);
+        queue.len = LenCounter::new(self.len.mode());
+        while let Some(value) = self.dequeue() {
+            queue.enqueue(value);
         }
+
+        queue
+    }
+
+    /// Switches how [`LockFreeQueue::len`] counts, e.g.
+    /// `LockFreeQueue::new().with_len_mode(LenMode::Exact)` in tests that
+    /// check `len` while other threads are still enqueueing and dequeueing.
+    pub fn with_len_mode(mut self, mode: LenMode) -> Self {
+        let len = LenCounter::new(mode);
+        len.add(self.len.get());
+        self.len = len;
+        self
     }
 
     pub fn enqueue(&self, value: T) {
+        let guard = &R::pin();
         let node = Box::into_raw(Box::new(Node {
-            value: Some(value),
-            next: AtomicPtr::new(std::ptr::null_mut()),
+            value: UnsafeCell::new(MaybeUninit::new(value)),
+            next: AtomicPtr::new(ptr::null_mut()),
         }));
 
+        self.len.add(1);
+        let mut step = 0;
         loop {
-            let tail = self.tail.load(Ordering::Acquire);
-            let next_node = unsafe { (*tail).next.load(Ordering::Acquire) };
-            if tail == self.tail.load(Ordering::Relaxed) {
-                if next_node.is_null() {
-                    if unsafe {
-                        (*tail)
-                            .next
-                            .compare_exchange(next_node, node, Ordering::Release, Ordering::Relaxed)
-                            .is_ok()
-                    } {
-                        self.tail
-                            .compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed)
-                            .ok();
-
-                        return;
-                    }
-                } else {
+            let tail = R::protect(guard, 0, &self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if !next.is_null() {
+                let _ =
                     self.tail
-                        .compare_exchange(tail, next_node, Ordering::Release, Ordering::Relaxed)
-                        .ok();
-                }
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+
+                continue;
             }
+
+            if unsafe {
+                (*tail)
+                    .next
+                    .compare_exchange(next, node, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
+            } {
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, node, Ordering::Release, Ordering::Relaxed);
+
+                return;
+            }
+
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
     pub fn dequeue(&self) -> Option<T> {
+        let guard = &R::pin();
+        let mut step = 0;
         loop {
-            let head = self.head.load(Ordering::Acquire);
-            let tail = self.tail.load(Ordering::Acquire);
-            let next = unsafe { (*head).next.load(Ordering::Acquire) };
-            if head == self.head.load(Ordering::Relaxed) {
-                if head == tail && next.is_null() {
-                    return None;
-                }
+            let head = R::protect(guard, 0, &self.head);
+            let next = R::protect(guard, 1, unsafe { &(*head).next });
+            if next.is_null() {
+                return None;
+            }
 
-                self.tail
-                    .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed)
-                    .ok();
-            } else {
-                let val = unsafe { (*next).value.take() };
-                if self
+            // `next` only stays linked while `head` is still the sentinel.
+            if self.head.load(Ordering::Acquire) == head
+                && self
                     .head
                     .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed)
                     .is_ok()
-                {
-                    unsafe {
-                        drop(Box::from_raw(head));
-                    }
+            {
+                // The tail may still point at the old sentinel. Move it on
+                // before retiring the node so `enqueue` never links onto it.
+                let tail = self.tail.load(Ordering::Relaxed);
+                if tail == head {
+                    let _ = self.tail.compare_exchange(
+                        tail,
+                        next,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    );
+                }
 
-                    return val;
+                self.len.sub(1);
+                unsafe {
+                    let value = (*next).value.with(|value| (*value).assume_init_read());
+                    R::retire(guard, head);
+                    return Some(value);
                 }
             }
+
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        let head = self.head.load(Ordering::Acquire);
+        let guard = &R::pin();
+        let head = R::protect(guard, 0, &self.head);
+
+        unsafe { (*head).next.load(Ordering::Acquire) }.is_null()
+    }
+
+    /// Counts the items, according to the [`LenMode`] the structure was
+    /// built with. The default approximate mode stays cheap under
+    /// contention but may be off while pushes and pops are running; see
+    /// [`LenMode`] for what each mode guarantees.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+}
+
+impl<T, B: Backoff> LockFreeQueue<T, Epoch, B> {
+    /// Returns the front element without removing it.
+    ///
+    /// The node behind the reference is kept alive by `guard`, but a
+    /// concurrent `dequeue` still moves the value out of it. Only `Copy`
+    /// values stay valid after that, hence the bound.
+    pub fn peek<'g>(&self, guard: &'g Guard) -> Option<&'g T>
+    where
+        T: Copy + Sync,
+    {
+        let head = Epoch::protect(guard, 0, &self.head);
         let next = unsafe { (*head).next.load(Ordering::Acquire) };
 
-        next.is_null()
+        unsafe { next.as_ref() }.map(|node| {
+            node.value
+                .with(|value| unsafe { (*value).assume_init_ref() })
+        })
+    }
+
+    /// Iterates from the front of the queue, with the same guarantees as
+    /// [`LockFreeQueue::peek`] for every element yielded.
+    pub fn iter<'g>(&self, guard: &'g Guard) -> Iter<'g, T>
+    where
+        T: Copy + Sync,
+    {
+        let head = Epoch::protect(guard, 0, &self.head);
+
+        Iter {
+            current: unsafe { (*head).next.load(Ordering::Acquire) },
+            _guard: PhantomData,
+        }
     }
+}
 
-    pub fn peek(&self) -> Option<&T> {
+impl<T, B: Backoff> LockFreeQueue<T, Hazard, B> {
+    /// Returns a copy of the front element without removing it.
+    ///
+    /// A hazard pointer only protects a node while the guard that set it is
+    /// alive, so unlike the epoch-backed queue this can't hand out a
+    /// reference.
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let guard = &Hazard::pin();
         loop {
-            let head = self.head.load(Ordering::Acquire);
-            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            let head = Hazard::protect(guard, 0, &self.head);
+            let next = Hazard::protect(guard, 1, unsafe { &(*head).next });
             if next.is_null() {
                 return None;
             }
 
-            if head == self.head.load(Ordering::Relaxed) {
-                return unsafe { (*next).value.as_ref() };
+            if self.head.load(Ordering::Acquire) == head {
+                return Some(unsafe { (*next).value.with(|value| (*value).assume_init_read()) });
             }
         }
     }
-
-    pub fn iter(&self) -> Iter<'_, T> {
-        Iter {
-            current: unsafe {
-                (*self.head.load(Ordering::Acquire))
-                    .next
-                    .load(Ordering::Acquire)
-            },
-            _marker: std::marker::PhantomData,
-        }
-    }
 }
 
-impl<T> Drop for LockFreeQueue<T> {
+impl<T, R: Reclaim, B: Backoff> Drop for LockFreeQueue<T, R, B> {
     fn drop(&mut self) {
-        while self.dequeue().is_some() {}
-        let head = self.head.load(Ordering::Relaxed);
-        unsafe {
-            drop(Box::from_raw(head));
+        let mut current = self.head.load(Ordering::Relaxed);
+        let sentinel = unsafe { Box::from_raw(current) };
+        current = sentinel.next.load(Ordering::Relaxed);
+        while !current.is_null() {
+            let node = unsafe { Box::from_raw(current) };
+            node.value
+                .with_mut(|value| unsafe { (*value).assume_init_drop() });
+            current = node.next.load(Ordering::Relaxed);
         }
     }
 }
 
-impl<T: Clone> Clone for LockFreeQueue<T> {
+// Copies values out under an epoch guard. A `T: Clone` bound would mean
+// calling `clone` on a value a concurrent `dequeue` may be moving out and
+// dropping at the same moment.
+impl<T: Copy + Sync, B: Backoff + Clone> Clone for LockFreeQueue<T, Epoch, B> {
     fn clone(&self) -> Self {
-        let new_queue = LockFreeQueue::new();
-        let mut current = unsafe {
-            (*(self.head.load(Ordering::Acquire)))
-                .next
-                .load(Ordering::Acquire)
-        };
-
-        while !current.is_null() {
-            new_queue.enqueue(unsafe { (*current).value.clone().unwrap() });
-            current = unsafe { (*current).next.load(Ordering::Acquire) };
+        let new_queue =
+            LockFreeQueue::with_parts(self.backoff.clone()).with_len_mode(self.len.mode());
+        let guard = &Epoch::pin();
+        for &value in self.iter(guard) {
+            new_queue.enqueue(value);
         }
 
         new_queue
     }
 }
 
-impl<T> Default for LockFreeQueue<T> {
+impl<T, R: Reclaim, B: Backoff> ConcurrentQueue<T> for LockFreeQueue<T, R, B> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.enqueue(item);
+        Ok(())
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.dequeue()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, R: Reclaim + Default, B: Backoff + Default> Default for LockFreeQueue<T, R, B> {
     fn default() -> Self {
-        Self::new()
+        Self::with_parts(B::default())
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> fmt::Debug for LockFreeQueue<T, R, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockFreeQueue")
+            .field("head", &self.head)
+            .field("tail", &self.tail)
+            .field("len", &self.len)
+            .finish()
     }
 }
 
-pub struct Iter<'a, T> {
+pub struct Iter<'g, T> {
     current: *const Node<T>,
-    _marker: std::marker::PhantomData<&'a T>,
+    _guard: PhantomData<(&'g Guard, &'g T)>,
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current.is_null() {
-            None
-        } else {
-            unsafe {
-                let result = &(*self.current).value;
-                self.current = (*self.current).next.load(Ordering::Acquire);
-
-                result.as_ref()
-            }
-        }
+        let node = unsafe { self.current.as_ref() }?;
+        self.current = node.next.load(Ordering::Acquire);
+
+        Some(
+            node.value
+                .with(|value| unsafe { (*value).assume_init_ref() }),
+        )
     }
 }
 
-unsafe impl<T: Send> Send for LockFreeQueue<T> {}
-unsafe impl<T: Sync> Sync for LockFreeQueue<T> {}
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Send> Send for LockFreeQueue<T, R, B> {}
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Sync> Sync for LockFreeQueue<T, R, B> {}
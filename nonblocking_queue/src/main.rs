@@ -1,10 +1,8 @@
-mod lockfree_queue;
-
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-use lockfree_queue::LockFreeQueue;
+use nonblocking_queue::{pin, spsc, Blocking, BlockingConcurrentQueue, LockFreeQueue};
 
 fn main() {
     let queue = LockFreeQueue::new();
@@ -20,18 +18,20 @@ fn main() {
     queue.enqueue(4);
     assert!(!queue.is_empty());
 
-    assert_eq!(queue.peek(), Some(&4));
+    let guard = pin();
+    assert_eq!(queue.peek(&guard), Some(&4));
     assert_eq!(queue.dequeue(), Some(4));
-    assert_eq!(queue.peek(), None);
+    assert_eq!(queue.peek(&guard), None);
 
     queue.enqueue(5);
     queue.enqueue(6);
     queue.enqueue(7);
-    let mut iter = queue.iter();
+    let mut iter = queue.iter(&guard);
     assert_eq!(iter.next(), Some(&5));
     assert_eq!(iter.next(), Some(&6));
     assert_eq!(iter.next(), Some(&7));
     assert_eq!(iter.next(), None);
+    drop(guard);
 
     let queue2 = queue.clone();
     assert_eq!(queue.dequeue(), Some(5));
@@ -74,25 +74,21 @@ fn main() {
 
     assert_eq!(total_sum, 49995000);
 
-    let queue = Arc::new(LockFreeQueue::new());
+    let queue = Arc::new(Blocking::new(LockFreeQueue::new()));
     let q_clone = Arc::clone(&queue);
 
     let producer = thread::spawn(move || {
         for i in 0..10000 {
-            q_clone.enqueue(i);
+            q_clone.push(i).unwrap();
             thread::sleep(Duration::from_nanos(1));
         }
+        q_clone.close();
     });
 
     let consumer = thread::spawn(move || {
         let mut sum = 0;
-        for _ in 0..10000 {
-            while queue.dequeue().is_none() {
-                thread::yield_now();
-            }
-            if let Some(val) = queue.dequeue() {
-                sum += val;
-            }
+        while let Ok(val) = queue.pop() {
+            sum += val;
         }
         sum
     });
@@ -101,5 +97,40 @@ fn main() {
     let sum = consumer.join().unwrap();
     assert_eq!(sum, 49995000);
 
+    let (mut producer, mut consumer) = spsc(4);
+    assert_eq!(producer.push_slice(&[1, 2, 3, 4, 5]), 4);
+    assert_eq!(producer.try_push(5), Err(5));
+    let mut buffer = [0; 3];
+    assert_eq!(consumer.pop_into(&mut buffer), 3);
+    assert_eq!(buffer, [1, 2, 3]);
+    assert_eq!(producer.try_push(5), Ok(()));
+    assert_eq!(consumer.try_pop(), Some(4));
+    assert_eq!(consumer.try_pop(), Some(5));
+    assert_eq!(consumer.try_pop(), None);
+
+    let (mut producer, mut consumer) = spsc(16);
+    let producer = thread::spawn(move || {
+        for i in 0..10000 {
+            let mut item = i;
+            while let Err(rejected) = producer.try_push(item) {
+                item = rejected;
+                thread::yield_now();
+            }
+        }
+    });
+    let mut sum = 0;
+    let mut received = 0;
+    while received < 10000 {
+        match consumer.try_pop() {
+            Some(val) => {
+                sum += val;
+                received += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    producer.join().unwrap();
+    assert_eq!(sum, 49995000);
+
     println!("All tests passed successfully!");
 }
@@ -0,0 +1,252 @@
+use concurrency_core::sync::atomic::{AtomicUsize, Ordering};
+use concurrency_core::sync::cell::UnsafeCell;
+use concurrency_core::sync::Arc;
+use std::fmt;
+use std::mem::MaybeUninit;
+
+/// Creates a wait-free single-producer/single-consumer queue that holds up to
+/// `capacity` items, split into its two ends.
+///
+/// Neither half can be cloned and every operation takes `&mut self`, so the
+/// type system guarantees one pushing thread and one popping thread. That
+/// lets both sides get by without a CAS: every push and pop finishes in a
+/// bounded number of steps, whatever the other side is doing.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn spsc<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(capacity > 0, "spsc capacity must be at least 1");
+
+    let shared = Arc::new(Shared {
+        head: Position(AtomicUsize::new(0)),
+        tail: Position(AtomicUsize::new(0)),
+        slots: (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+    });
+    let producer = Producer {
+        shared: Arc::clone(&shared),
+        tail: 0,
+        cached_head: 0,
+    };
+    let consumer = Consumer {
+        shared,
+        head: 0,
+        cached_tail: 0,
+    };
+
+    (producer, consumer)
+}
+
+// `head` and `tail` count every pop and push so far and wrap around the
+// slots. Only the consumer stores `head` and only the producer stores
+// `tail`. Each side keeps its own index in a plain field and caches the last
+// value it read of the other's, so it only touches the other side's cache
+// line when the cached value says there isn't enough room (or items).
+struct Shared<T> {
+    head: Position,
+    tail: Position,
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+#[repr(align(64))]
+struct Position(AtomicUsize);
+
+impl<T> Shared<T> {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, index: usize) -> &UnsafeCell<MaybeUninit<T>> {
+        &self.slots[index % self.slots.len()]
+    }
+
+    fn len(&self) -> usize {
+        let head = self.head.0.load(Ordering::Acquire);
+        let tail = self.tail.0.load(Ordering::Acquire);
+        tail.wrapping_sub(head).min(self.capacity())
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let mut index = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        while index != tail {
+            self.slot(index)
+                .with_mut(|value| unsafe { (*value).assume_init_drop() });
+            index = index.wrapping_add(1);
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+/// The pushing end of a queue made by [`spsc`].
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+    tail: usize,
+    cached_head: usize,
+}
+
+impl<T> Producer<T> {
+    /// Hands `value` back if the queue is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.free(1) == 0 {
+            return Err(value);
+        }
+
+        self.shared
+            .slot(self.tail)
+            .with_mut(|slot| unsafe { (*slot).write(value) });
+        self.publish(1);
+
+        Ok(())
+    }
+
+    /// Pushes as many items from the front of `items` as there is room for
+    /// and returns how many that was, making them visible to the consumer
+    /// all at once.
+    pub fn push_slice(&mut self, items: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let count = items.len().min(self.free(items.len()));
+        for (offset, item) in items[..count].iter().enumerate() {
+            self.shared
+                .slot(self.tail.wrapping_add(offset))
+                .with_mut(|slot| unsafe { (*slot).write(*item) });
+        }
+        self.publish(count);
+
+        count
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Counts the items, which can only have dropped by the time it returns.
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    // Only re-reads the consumer's index once the cached one says there's
+    // less room than `wanted`.
+    fn free(&mut self, wanted: usize) -> usize {
+        let capacity = self.shared.capacity();
+        let mut free = capacity - self.tail.wrapping_sub(self.cached_head);
+        if free < wanted {
+            self.cached_head = self.shared.head.0.load(Ordering::Acquire);
+            free = capacity - self.tail.wrapping_sub(self.cached_head);
+        }
+
+        free
+    }
+
+    fn publish(&mut self, count: usize) {
+        self.tail = self.tail.wrapping_add(count);
+        self.shared.tail.0.store(self.tail, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// The popping end of a queue made by [`spsc`].
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+    head: usize,
+    cached_tail: usize,
+}
+
+impl<T> Consumer<T> {
+    /// Returns `None` if the queue is empty.
+    pub fn try_pop(&mut self) -> Option<T> {
+        if self.available(1) == 0 {
+            return None;
+        }
+
+        let value = self
+            .shared
+            .slot(self.head)
+            .with(|slot| unsafe { (*slot).assume_init_read() });
+        self.release(1);
+
+        Some(value)
+    }
+
+    /// Pops into the front of `buffer` until it is full or the queue is
+    /// empty and returns how many items that was, freeing their slots for
+    /// the producer all at once.
+    pub fn pop_into(&mut self, buffer: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        let count = buffer.len().min(self.available(buffer.len()));
+        for (offset, item) in buffer[..count].iter_mut().enumerate() {
+            *item = self
+                .shared
+                .slot(self.head.wrapping_add(offset))
+                .with(|slot| unsafe { (*slot).assume_init_read() });
+        }
+        self.release(count);
+
+        count
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Counts the items, which can only have grown by the time it returns.
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Only re-reads the producer's index once the cached one says there are
+    // fewer than `wanted` items.
+    fn available(&mut self, wanted: usize) -> usize {
+        let mut available = self.cached_tail.wrapping_sub(self.head);
+        if available < wanted {
+            self.cached_tail = self.shared.tail.0.load(Ordering::Acquire);
+            available = self.cached_tail.wrapping_sub(self.head);
+        }
+
+        available
+    }
+
+    fn release(&mut self, count: usize) {
+        self.head = self.head.wrapping_add(count);
+        self.shared.head.0.store(self.head, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
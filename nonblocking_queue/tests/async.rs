@@ -0,0 +1,181 @@
+use futures_core::Stream;
+use futures_sink::Sink;
+use nonblocking_queue::{AsyncQueue, PopError, PushError};
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+// Just enough executor to drive one future on the current thread.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl CountingWaker {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+    let counter = Arc::new(CountingWaker::default());
+    (Arc::clone(&counter), Waker::from(counter))
+}
+
+#[test]
+fn pop_waits_for_push_from_thread() {
+    let queue = Arc::new(AsyncQueue::new());
+    let producer_queue = Arc::clone(&queue);
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        producer_queue.push(1).unwrap();
+    });
+
+    assert_eq!(block_on(queue.pop()), Ok(1));
+    producer.join().unwrap();
+}
+
+#[test]
+fn dropped_pop_passes_its_wakeup_on() {
+    let queue = AsyncQueue::new();
+    let (first_count, first_waker) = counting_waker();
+    let (second_count, second_waker) = counting_waker();
+    let mut first = Box::pin(queue.pop());
+    let mut second = Box::pin(queue.pop());
+
+    assert!(first
+        .as_mut()
+        .poll(&mut Context::from_waker(&first_waker))
+        .is_pending());
+    assert!(second
+        .as_mut()
+        .poll(&mut Context::from_waker(&second_waker))
+        .is_pending());
+
+    queue.push(1).unwrap();
+    assert_eq!((first_count.count(), second_count.count()), (1, 0));
+
+    drop(first);
+    assert_eq!(second_count.count(), 1);
+    assert_eq!(
+        second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker)),
+        Poll::Ready(Ok(1))
+    );
+}
+
+#[test]
+fn close_wakes_pending_pop() {
+    let queue = AsyncQueue::<i32>::new();
+    let (count, waker) = counting_waker();
+    let mut pop = Box::pin(queue.pop());
+    let mut cx = Context::from_waker(&waker);
+    assert!(pop.as_mut().poll(&mut cx).is_pending());
+
+    queue.close();
+    assert_eq!(count.count(), 1);
+    assert_eq!(
+        pop.as_mut().poll(&mut cx),
+        Poll::Ready(Err(PopError::Closed))
+    );
+    assert_eq!(queue.push(1), Err(PushError::Closed(1)));
+}
+
+#[test]
+fn stream_and_sink_move_every_item() {
+    let queue = Arc::new(AsyncQueue::new());
+    let mut stream = Arc::clone(&queue).into_stream();
+    let producers: Vec<_> = (0..4)
+        .map(|p| {
+            let mut sink = Arc::clone(&queue).into_sink();
+            thread::spawn(move || {
+                let (_, waker) = counting_waker();
+                let mut cx = Context::from_waker(&waker);
+                for i in 0..1000 {
+                    assert!(Pin::new(&mut sink).poll_ready(&mut cx).is_ready());
+                    Pin::new(&mut sink).start_send(p * 1000 + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let mut sink = Arc::clone(&queue).into_sink();
+    let (_, waker) = counting_waker();
+    assert_eq!(
+        Pin::new(&mut sink).poll_close(&mut Context::from_waker(&waker)),
+        Poll::Ready(Ok(()))
+    );
+
+    let mut items = Vec::new();
+    while let Some(item) = block_on(poll_fn(|cx| Pin::new(&mut stream).poll_next(cx))) {
+        items.push(item);
+    }
+    items.sort();
+
+    assert_eq!(items, (0..4000).collect::<Vec<_>>());
+}
+
+#[test]
+fn parked_consumers_see_every_item() {
+    let queue = Arc::new(AsyncQueue::new());
+    let consumers: Vec<_> = (0..4)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                let mut sum = 0u64;
+                while let Ok(value) = block_on(queue.pop()) {
+                    sum += value;
+                }
+                sum
+            })
+        })
+        .collect();
+    let producers: Vec<_> = (0..4)
+        .map(|p| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                for i in 0..10_000 {
+                    queue.push(p * 10_000 + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    queue.close();
+
+    let total: u64 = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+    assert_eq!(total, (0..40_000).sum());
+}
@@ -0,0 +1,66 @@
+use nonblocking_queue::{LenMode, LockFreeQueue};
+use std::thread;
+
+const THREADS: u32 = 4;
+const OPS_PER_THREAD: u32 = 2_000;
+
+#[test]
+fn with_len_mode_carries_the_count_over() {
+    let queue = LockFreeQueue::new();
+    queue.enqueue(1);
+    queue.enqueue(2);
+
+    let queue = queue.with_len_mode(LenMode::Exact);
+    assert_eq!(queue.len(), 2);
+    queue.enqueue(3);
+    let queue = queue.with_len_mode(LenMode::Approximate);
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.dequeue(), Some(1));
+    assert_eq!(queue.len(), 2);
+}
+
+#[test]
+fn len_is_exact_once_pushes_and_pops_return() {
+    for mode in [LenMode::Approximate, LenMode::Exact] {
+        let queue = LockFreeQueue::new().with_len_mode(mode);
+        thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for value in 0..OPS_PER_THREAD {
+                        queue.enqueue(value);
+                        if (value + thread) % 2 == 0 {
+                            queue.dequeue();
+                        }
+                    }
+                });
+            }
+        });
+
+        let remaining = THREADS * OPS_PER_THREAD / 2;
+        assert_eq!(queue.len(), remaining as usize);
+        assert_eq!(
+            queue.iter(&nonblocking_queue::pin()).count(),
+            remaining as usize
+        );
+    }
+}
+
+#[test]
+fn exact_len_never_runs_ahead_of_started_pushes() {
+    let queue = LockFreeQueue::new().with_len_mode(LenMode::Exact);
+    let total = (THREADS * OPS_PER_THREAD) as usize;
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for value in 0..OPS_PER_THREAD {
+                    queue.enqueue(value);
+                }
+            });
+        }
+        while queue.len() < total {
+            assert!(queue.len() <= total);
+        }
+    });
+    assert_eq!(queue.len(), total);
+}
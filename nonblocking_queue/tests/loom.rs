@@ -0,0 +1,126 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p nonblocking_queue --release --test loom`.
+use loom::sync::Arc;
+use loom::thread;
+use nonblocking_queue::{spsc, Blocking, BlockingConcurrentQueue, LockFreeQueue};
+
+#[test]
+fn concurrent_enqueues_both_land() {
+    loom::model(|| {
+        let queue = Arc::new(LockFreeQueue::new());
+        let producers: Vec<_> = (1..=2)
+            .map(|value| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || queue.enqueue(value))
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut values = vec![queue.dequeue().unwrap(), queue.dequeue().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(queue.dequeue(), None);
+    });
+}
+
+#[test]
+fn dequeue_racing_enqueue_keeps_fifo_order() {
+    loom::model(|| {
+        let queue = Arc::new(LockFreeQueue::new());
+        let producer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                queue.enqueue(1);
+                queue.enqueue(2);
+            })
+        };
+
+        let mut values: Vec<_> = queue.dequeue().into_iter().collect();
+        producer.join().unwrap();
+        while let Some(value) = queue.dequeue() {
+            values.push(value);
+        }
+        assert_eq!(values, [1, 2]);
+    });
+}
+
+#[test]
+fn racing_dequeues_take_each_item_once() {
+    loom::model(|| {
+        let queue = Arc::new(LockFreeQueue::new());
+        queue.enqueue(1);
+        queue.enqueue(2);
+
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.dequeue())
+        };
+        let mut values = vec![queue.dequeue().unwrap(), consumer.join().unwrap().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert!(queue.is_empty());
+    });
+}
+
+#[test]
+fn blocking_pop_is_woken_by_push() {
+    loom::model(|| {
+        let queue = Arc::new(Blocking::new(LockFreeQueue::new()));
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.pop())
+        };
+
+        queue.push(1).unwrap();
+        assert_eq!(consumer.join().unwrap(), Ok(1));
+    });
+}
+
+#[test]
+fn spsc_pop_racing_push_sees_a_whole_item() {
+    loom::model(|| {
+        let (mut producer, mut consumer) = spsc(1);
+        let consumer = thread::spawn(move || (consumer.try_pop(), consumer));
+
+        producer.try_push(String::from("item")).unwrap();
+        let (popped, mut consumer) = consumer.join().unwrap();
+        let popped = popped.or_else(|| consumer.try_pop());
+        assert_eq!(popped.as_deref(), Some("item"));
+    });
+}
+
+#[test]
+fn spsc_push_into_a_freed_slot_waits_for_the_pop() {
+    loom::model(|| {
+        let (mut producer, mut consumer) = spsc(1);
+        producer.try_push(String::from("first")).unwrap();
+        let consumer = thread::spawn(move || (consumer.try_pop(), consumer));
+
+        let second = producer.try_push(String::from("second"));
+        let (popped, mut consumer) = consumer.join().unwrap();
+        assert_eq!(popped.as_deref(), Some("first"));
+        if let Err(second) = second {
+            producer.try_push(second).unwrap();
+        }
+        assert_eq!(consumer.try_pop().as_deref(), Some("second"));
+    });
+}
+
+#[test]
+fn spsc_pop_into_racing_push_slice_sees_a_prefix() {
+    loom::model(|| {
+        let (mut producer, mut consumer) = spsc(2);
+        let consumer = thread::spawn(move || {
+            let mut buffer = [0; 2];
+            let count = consumer.pop_into(&mut buffer);
+            buffer[..count].to_vec()
+        });
+
+        assert_eq!(producer.push_slice(&[1, 2, 3]), 2);
+        let popped = consumer.join().unwrap();
+        assert!(popped.is_empty() || popped == [1, 2]);
+    });
+}
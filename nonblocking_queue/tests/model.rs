@@ -0,0 +1,110 @@
+use nonblocking_queue::{pin, spsc, LenMode, LockFreeQueue, Spin};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::collections::VecDeque;
+
+// Runs random single-threaded operation sequences against the queue and a
+// `VecDeque`, checking every result. proptest shrinks a failing sequence
+// down to the shortest one that still fails.
+#[derive(Debug, Clone)]
+enum Op {
+    Enqueue(u8),
+    Dequeue,
+    Peek,
+    Iter,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u8>().prop_map(Op::Enqueue),
+        2 => Just(Op::Dequeue),
+        1 => Just(Op::Peek),
+        1 => Just(Op::Iter),
+    ]
+}
+
+#[derive(Debug, Clone)]
+enum SpscOp {
+    Push(u8),
+    Pop,
+    PushSlice(Vec<u8>),
+    PopInto(usize),
+}
+
+fn spsc_op() -> impl Strategy<Value = SpscOp> {
+    prop_oneof![
+        3 => any::<u8>().prop_map(SpscOp::Push),
+        2 => Just(SpscOp::Pop),
+        1 => vec(any::<u8>(), 0..8).prop_map(SpscOp::PushSlice),
+        1 => (0..8usize).prop_map(SpscOp::PopInto),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn lockfree_queue_matches_vecdeque(ops in vec(op(), 0..64), exact in any::<bool>()) {
+        let mode = if exact { LenMode::Exact } else { LenMode::Approximate };
+        let queue = LockFreeQueue::new().with_len_mode(mode);
+        let mut model = VecDeque::new();
+        for op in ops {
+            match op {
+                Op::Enqueue(value) => {
+                    queue.enqueue(value);
+                    model.push_back(value);
+                }
+                Op::Dequeue => prop_assert_eq!(queue.dequeue(), model.pop_front()),
+                Op::Peek => prop_assert_eq!(queue.peek(&pin()).copied(), model.front().copied()),
+                Op::Iter => {
+                    let items: Vec<_> = queue.iter(&pin()).copied().collect();
+                    prop_assert_eq!(items, Vec::from(model.clone()));
+                }
+            }
+            prop_assert_eq!(queue.len(), model.len());
+            prop_assert_eq!(queue.is_empty(), model.is_empty());
+        }
+    }
+
+    #[test]
+    fn with_backoff_keeps_items_in_order(items in vec(any::<u8>(), 0..32)) {
+        let queue = LockFreeQueue::new();
+        for &item in &items {
+            queue.enqueue(item);
+        }
+        let queue = queue.with_backoff(Spin::default());
+        prop_assert_eq!(queue.iter(&pin()).copied().collect::<Vec<_>>(), items);
+    }
+
+    #[test]
+    fn spsc_matches_bounded_vecdeque(ops in vec(spsc_op(), 0..64), capacity in 1..8usize) {
+        let (mut producer, mut consumer) = spsc(capacity);
+        let mut model = VecDeque::new();
+        for op in ops {
+            match op {
+                SpscOp::Push(value) => {
+                    if model.len() < capacity {
+                        prop_assert_eq!(producer.try_push(value), Ok(()));
+                        model.push_back(value);
+                    } else {
+                        prop_assert_eq!(producer.try_push(value), Err(value));
+                    }
+                }
+                SpscOp::Pop => prop_assert_eq!(consumer.try_pop(), model.pop_front()),
+                SpscOp::PushSlice(items) => {
+                    let count = items.len().min(capacity - model.len());
+                    prop_assert_eq!(producer.push_slice(&items), count);
+                    model.extend(&items[..count]);
+                }
+                SpscOp::PopInto(len) => {
+                    let mut buffer = vec![0; len];
+                    let count = consumer.pop_into(&mut buffer);
+                    let expected: Vec<_> = model.drain(..len.min(model.len())).collect();
+                    prop_assert_eq!(&buffer[..count], &expected[..]);
+                }
+            }
+            prop_assert_eq!(producer.len(), model.len());
+            prop_assert_eq!(consumer.len(), model.len());
+            prop_assert_eq!(producer.is_full(), model.len() == capacity);
+            prop_assert_eq!(consumer.is_empty(), model.is_empty());
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use concurrency_core::random;
+use concurrency_core::sync::atomic::{AtomicPtr, Ordering};
+use concurrency_core::sync::hint;
+use std::ptr::{self, NonNull};
+
+// How many times a push re-reads its slot before withdrawing its offer.
+// Kept tiny under loom, where every read is a scheduling point.
+const OFFER_SPINS: usize = if cfg!(loom) { 2 } else { 64 };
+
+// An exchange array in the style of Hendler, Shavit and Yerushalmi. A push
+// that lost a race on the head parks its node in a random empty slot and
+// waits for a while; a pop that lost a race looks at a random slot and, if a
+// node is parked there, claims it by swapping in `taken()`. The pair cancels
+// out without either of them touching the head, and the push is linearized
+// right before the pop at the moment of the claim.
+//
+// A slot only ever goes empty -> node (the pusher offers), node -> empty
+// (the pusher withdraws) or node -> taken -> empty (a popper claims it, then
+// the pusher sees that and clears the slot). A popper never dereferences a
+// node it hasn't claimed, so claimed nodes are handed over whole and can be
+// freed in place.
+pub(crate) struct EliminationArray<P> {
+    slots: Box<[Slot<P>]>,
+}
+
+#[repr(align(64))]
+struct Slot<P>(AtomicPtr<P>);
+
+impl<P> EliminationArray<P> {
+    pub(crate) fn new(slots: usize) -> Self {
+        Self {
+            slots: (0..slots)
+                .map(|_| Slot(AtomicPtr::new(ptr::null_mut())))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.slots.is_empty()
+    }
+
+    /// Offers `item` to a concurrent `take`. Returns `true` if a pop claimed
+    /// it, in which case the caller no longer owns `item`.
+    pub(crate) fn offer(&self, item: *mut P) -> bool {
+        let slot = &self.slot().0;
+        if slot
+            .compare_exchange(ptr::null_mut(), item, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+
+        for _ in 0..OFFER_SPINS {
+            if slot.load(Ordering::Acquire) != item {
+                slot.store(ptr::null_mut(), Ordering::Release);
+                return true;
+            }
+            hint::spin_loop();
+        }
+
+        match slot.compare_exchange(item, ptr::null_mut(), Ordering::Relaxed, Ordering::Acquire) {
+            Ok(_) => false,
+            Err(_) => {
+                slot.store(ptr::null_mut(), Ordering::Release);
+                true
+            }
+        }
+    }
+
+    /// Claims whatever is on offer in a random slot.
+    pub(crate) fn take(&self) -> Option<*mut P> {
+        let slot = &self.slot().0;
+        let item = slot.load(Ordering::Relaxed);
+        if item.is_null() || item == taken() {
+            return None;
+        }
+
+        slot.compare_exchange(item, taken(), Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+    }
+
+    fn slot(&self) -> &Slot<P> {
+        &self.slots[random::next_u32() as usize % self.slots.len()]
+    }
+}
+
+// Dangling, so never the address of a live node.
+fn taken<P>() -> *mut P {
+    NonNull::dangling().as_ptr()
+}
@@ -0,0 +1,9 @@
+mod elimination;
+mod lockfree_stack;
+
+pub use concurrency_core::{
+    Backoff, Blocking, BlockingConcurrentStack, ConcurrentStack, Epoch, Exponential, Hazard,
+    LenMode, NoBackoff, Reclaim, Spin, SpinThenYield,
+};
+pub use crossbeam_epoch::{pin, Guard};
+pub use lockfree_stack::{Iter, LockFreeStack};
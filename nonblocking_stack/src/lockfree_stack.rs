@@ -1,29 +1,97 @@
+use crate::elimination::EliminationArray;
+use concurrency_core::sync::atomic::{AtomicPtr, Ordering};
+use concurrency_core::sync::cell::UnsafeCell;
+use concurrency_core::{
+    Backoff, ConcurrentStack, Epoch, Hazard, LenCounter, LenMode, Reclaim, SpinThenYield,
+};
+use crossbeam_epoch::Guard;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
 use std::ptr;
-use std::sync::atomic::{AtomicPtr, Ordering};
 
-pub struct LockFreeStack<T> {
+pub struct LockFreeStack<T, R: Reclaim = Epoch, B: Backoff = SpinThenYield> {
     head: AtomicPtr<Node<T>>,
+    len: LenCounter,
+    elimination: EliminationArray<Node<T>>,
+    backoff: B,
+    _reclaim: PhantomData<R>,
 }
 
+// Popped nodes are retired through `R` rather than freed in place, so a
+// node's address can't be reused while any thread that might have loaded it
+// still protects it. That is what keeps the head CAS free of ABA.
 struct Node<T> {
     next: AtomicPtr<Node<T>>,
-    value: T,
+    value: UnsafeCell<ManuallyDrop<T>>,
 }
 
 impl<T> LockFreeStack<T> {
     pub fn new() -> Self {
+        Self::with_reclaim(Epoch)
+    }
+}
+
+impl<T, R: Reclaim> LockFreeStack<T, R> {
+    /// Creates a stack that frees popped nodes through `R`, e.g.
+    /// `LockFreeStack::with_reclaim(Hazard)`.
+    pub fn with_reclaim(_reclaim: R) -> Self {
         LockFreeStack {
             head: AtomicPtr::new(ptr::null_mut()),
+            len: LenCounter::default(),
+            elimination: EliminationArray::new(0),
+            backoff: SpinThenYield::default(),
+            _reclaim: PhantomData,
         }
     }
+}
+
+impl<T, R: Reclaim, B: Backoff> LockFreeStack<T, R, B> {
+    /// Switches to another [`Backoff`] strategy for retrying after a lost
+    /// race, e.g. `LockFreeStack::new().with_backoff(Spin::new(4))`. Items
+    /// already on the stack move over in order.
+    pub fn with_backoff<B2: Backoff>(mut self, backoff: B2) -> LockFreeStack<T, R, B2> {
+        let stack = LockFreeStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            len: LenCounter::new(self.len.mode()),
+            elimination: std::mem::replace(&mut self.elimination, EliminationArray::new(0)),
+            backoff,
+            _reclaim: PhantomData,
+        };
+        stack.push_range(self.try_pop_range(self.len()));
+
+        stack
+    }
+
+    /// Adds an elimination array with `slots` slots, e.g.
+    /// `LockFreeStack::new().with_elimination(8)`.
+    ///
+    /// A `push` and a `try_pop` that both lose a race on the head can then
+    /// meet in the array and hand the value over directly, which takes load
+    /// off the head under heavy contention. Without contention it costs
+    /// nothing. Zero slots turns it off again.
+    pub fn with_elimination(mut self, slots: usize) -> Self {
+        self.elimination = EliminationArray::new(slots);
+        self
+    }
+
+    /// Switches how [`LockFreeStack::len`] counts, e.g.
+    /// `LockFreeStack::new().with_len_mode(LenMode::Exact)` in tests that
+    /// check `len` while other threads are still pushing and popping.
+    pub fn with_len_mode(mut self, mode: LenMode) -> Self {
+        let len = LenCounter::new(mode);
+        len.add(self.len.get());
+        self.len = len;
+        self
+    }
 
     pub fn push(&self, value: T) {
         let new_node = Box::into_raw(Box::new(Node {
-            value,
+            value: UnsafeCell::new(ManuallyDrop::new(value)),
             next: AtomicPtr::new(ptr::null_mut()),
         }));
 
-        let mut backoff: u64 = 1;
+        self.len.add(1);
+        let mut step = 0;
         loop {
             let curr_head = self.head.load(Ordering::Acquire);
             unsafe { (*new_node).next.store(curr_head, Ordering::Relaxed) };
@@ -35,11 +103,13 @@ impl<T> LockFreeStack<T> {
                 break;
             }
 
-            for _ in 0..backoff {
-                std::thread::yield_now();
+            // An offer nobody took, or couldn't place, still backs off.
+            if self.elimination.is_enabled() && self.elimination.offer(new_node) {
+                break;
             }
 
-            backoff = backoff.saturating_mul(2);
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
@@ -48,22 +118,24 @@ impl<T> LockFreeStack<T> {
             return;
         }
 
+        let count = items.len();
         let mut new_head = Box::into_raw(Box::new(Node {
-            value: items.pop().unwrap(),
+            value: UnsafeCell::new(ManuallyDrop::new(items.pop().unwrap())),
             next: AtomicPtr::new(ptr::null_mut()),
         }));
 
         let tail = new_head;
         while let Some(item) = items.pop() {
             let new_node = Box::into_raw(Box::new(Node {
-                value: item,
+                value: UnsafeCell::new(ManuallyDrop::new(item)),
                 next: AtomicPtr::new(new_head),
             }));
-            
+
             new_head = new_node;
         }
 
-        let mut backoff: u64 = 1;
+        self.len.add(count);
+        let mut step = 0;
         loop {
             let curr_head = self.head.load(Ordering::Acquire);
             unsafe { (*tail).next.store(curr_head, Ordering::Relaxed) };
@@ -75,18 +147,16 @@ impl<T> LockFreeStack<T> {
                 break;
             }
 
-            for _ in 0..backoff {
-                std::thread::yield_now();
-            }
-
-            backoff = backoff.saturating_mul(2);
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
     pub fn try_pop(&self) -> Option<T> {
-        let mut backoff: u64 = 1;
+        let guard = &R::pin();
+        let mut step = 0;
         loop {
-            let curr_head = self.head.load(Ordering::Acquire);
+            let curr_head = R::protect(guard, 0, &self.head);
             if curr_head.is_null() {
                 return None;
             }
@@ -97,31 +167,51 @@ impl<T> LockFreeStack<T> {
                 .compare_exchange(curr_head, next_node, Ordering::Release, Ordering::Relaxed)
                 .is_ok()
             {
-                let old_node = unsafe { Box::from_raw(curr_head) };
-                return Some(old_node.value);
+                self.len.sub(1);
+                unsafe {
+                    let value = (*curr_head).value.with(|value| ptr::read(&**value));
+                    R::retire(guard, curr_head);
+                    return Some(value);
+                }
             }
 
-            for _ in 0..backoff {
-                std::thread::yield_now();
+            if self.elimination.is_enabled() {
+                if let Some(node) = self.elimination.take() {
+                    // The node never made it onto the stack, so nobody else
+                    // can be looking at it.
+                    self.len.sub(1);
+                    let node = unsafe { Box::from_raw(node) };
+                    return Some(ManuallyDrop::into_inner(node.value.into_inner()));
+                }
             }
 
-            backoff = backoff.saturating_mul(2);
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
     }
 
     pub fn try_pop_range(&self, count: usize) -> Vec<T> {
+        let guard = &R::pin();
         let mut result = Vec::with_capacity(count);
-        let mut backoff: u64 = 1;
-        loop {
-            let curr_head = self.head.load(Ordering::Acquire);
+        let mut step = 0;
+        'retry: loop {
+            let curr_head = R::protect(guard, 0, &self.head);
             if curr_head.is_null() {
                 break;
             }
 
+            // The chain below a head that hasn't been popped never changes
+            // and none of it can be retired, so each node is safe to read as
+            // long as `curr_head` is still on top once it's protected. Slots
+            // 1 and 2 alternate so the node being read stays protected.
             let mut next = curr_head;
             let mut nodes_count = 0;
             while nodes_count < count && !next.is_null() {
-                next = unsafe { (*next).next.load(Ordering::Acquire) };
+                next = R::protect(guard, 1 + nodes_count % 2, unsafe { &(*next).next });
+                if self.head.load(Ordering::Acquire) != curr_head {
+                    continue 'retry;
+                }
+
                 nodes_count += 1;
             }
 
@@ -132,99 +222,160 @@ impl<T> LockFreeStack<T> {
             {
                 let mut current = curr_head;
                 for _ in 0..nodes_count {
-                    let node = unsafe { Box::from_raw(current) };
-                    result.push(node.value);
-                    current = node.next.load(Ordering::Relaxed);
+                    unsafe {
+                        result.push((*current).value.with(|value| ptr::read(&**value)));
+                        let popped = current;
+                        current = (*current).next.load(Ordering::Relaxed);
+                        R::retire(guard, popped);
+                    }
                 }
+                self.len.sub(nodes_count);
                 break;
             }
 
-            for _ in 0..backoff {
-                std::thread::yield_now();
-            }
-
-            backoff = backoff.saturating_mul(2);
+            self.backoff.wait(step);
+            step = step.saturating_add(1);
         }
 
         result
     }
 
-    pub fn try_peek(&self) -> Option<&T> {
-        let curr_head = self.head.load(Ordering::Relaxed);
-        if curr_head.is_null() {
-            None
-        } else {
-            unsafe { Some(&(*curr_head).value) }
-        }
-    }
-
     pub fn is_empty(&self) -> bool {
         self.head.load(Ordering::Relaxed).is_null()
     }
 
+    /// Counts the items, according to the [`LenMode`] the structure was
+    /// built with. The default approximate mode stays cheap under
+    /// contention but may be off while pushes and pops are running; see
+    /// [`LenMode`] for what each mode guarantees.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
     pub fn clear(&self) {
         while self.try_pop().is_some() {}
     }
+}
 
-    pub fn to_vec(&self) -> Vec<T>
+impl<T, B: Backoff> LockFreeStack<T, Epoch, B> {
+    /// Returns the top element without removing it.
+    ///
+    /// The node behind the reference is kept alive by `guard`, but a
+    /// concurrent `try_pop` still moves the value out of it. Only `Copy`
+    /// values stay valid after that, hence the bound.
+    pub fn try_peek<'g>(&self, guard: &'g Guard) -> Option<&'g T>
     where
-        T: Clone,
+        T: Copy + Sync,
     {
-        let mut result = Vec::new();
-        let mut curr_head = self.head.load(Ordering::Acquire);
-        while !curr_head.is_null() {
-            unsafe {
-                result.push((*curr_head).value.clone());
-                curr_head = (*curr_head).next.load(Ordering::Acquire);
-            }
-        }
+        let curr_head = Epoch::protect(guard, 0, &self.head);
 
-        result
+        unsafe { curr_head.as_ref() }.map(|node| node.value.with(|value| unsafe { &**value }))
     }
 
-    pub fn iter(&self) -> Iter<'_, T> {
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Copy + Sync,
+    {
+        let guard = &Epoch::pin();
+        self.iter(guard).copied().collect()
+    }
+
+    /// Iterates from the top of the stack, with the same guarantees as
+    /// [`LockFreeStack::try_peek`] for every element yielded.
+    pub fn iter<'g>(&self, guard: &'g Guard) -> Iter<'g, T>
+    where
+        T: Copy + Sync,
+    {
         Iter {
-            current: self.head.load(Ordering::Acquire),
-            _marker: std::marker::PhantomData,
+            current: Epoch::protect(guard, 0, &self.head),
+            _guard: PhantomData,
         }
     }
 }
 
-impl<T> Drop for LockFreeStack<T> {
+impl<T, B: Backoff> LockFreeStack<T, Hazard, B> {
+    /// Returns a copy of the top element without removing it.
+    ///
+    /// A hazard pointer only protects a node while the guard that set it is
+    /// alive, so unlike the epoch-backed stack this can't hand out a
+    /// reference.
+    pub fn try_peek(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let guard = &Hazard::pin();
+        let curr_head = Hazard::protect(guard, 0, &self.head);
+
+        unsafe { curr_head.as_ref() }.map(|node| node.value.with(|value| unsafe { **value }))
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> ConcurrentStack<T> for LockFreeStack<T, R, B> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.push(item);
+        Ok(())
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, R: Reclaim + Default, B: Backoff + Default> Default for LockFreeStack<T, R, B> {
+    fn default() -> Self {
+        LockFreeStack::with_reclaim(R::default()).with_backoff(B::default())
+    }
+}
+
+impl<T, R: Reclaim, B: Backoff> Drop for LockFreeStack<T, R, B> {
     fn drop(&mut self) {
-        self.clear();
+        let mut current = self.head.load(Ordering::Relaxed);
+        while !current.is_null() {
+            let node = unsafe { Box::from_raw(current) };
+            node.value
+                .with_mut(|value| unsafe { ManuallyDrop::drop(&mut *value) });
+            current = node.next.load(Ordering::Relaxed);
+        }
     }
 }
 
-impl<T> IntoIterator for LockFreeStack<T>
-where
-    T: Clone,
-{
+impl<T, R: Reclaim, B: Backoff> IntoIterator for LockFreeStack<T, R, B> {
     type Item = T;
     type IntoIter = std::vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.to_vec().into_iter()
+        let mut items = Vec::with_capacity(self.len());
+        while let Some(item) = self.try_pop() {
+            items.push(item);
+        }
+
+        items.into_iter()
     }
 }
 
-pub struct Iter<'a, T> {
+pub struct Iter<'g, T> {
     current: *const Node<T>,
-    _marker: std::marker::PhantomData<&'a T>,
+    _guard: PhantomData<(&'g Guard, &'g T)>,
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current.is_null() {
-            None
-        } else {
-            unsafe {
-                let node = &*self.current;
-                self.current = node.next.load(Ordering::Acquire) as *const Node<T>;
-                Some(&node.value)
-            }
-        }
+        let node = unsafe { self.current.as_ref() }?;
+        self.current = node.next.load(Ordering::Acquire);
+
+        Some(node.value.with(|value| unsafe { &**value }))
     }
 }
+
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Send> Send for LockFreeStack<T, R, B> {}
+unsafe impl<T: Send, R: Reclaim, B: Backoff + Sync> Sync for LockFreeStack<T, R, B> {}
@@ -1,9 +1,7 @@
-mod lockfree_stack;
-
 use std::sync::Arc;
 use std::thread;
 
-use lockfree_stack::LockFreeStack;
+use nonblocking_stack::{pin, LockFreeStack};
 
 fn main() {
     println!("Running single-threaded tests...");
@@ -16,7 +14,7 @@ fn main() {
     assert_eq!(stack.try_pop(), Some(2));
 
     stack.push(4);
-    assert_eq!(stack.try_peek(), Some(&4));
+    assert_eq!(stack.try_peek(&pin()), Some(&4));
     assert_eq!(stack.try_pop(), Some(4));
 
     assert!(!stack.is_empty());
@@ -35,11 +33,13 @@ fn main() {
     let vec = stack.to_vec();
     assert_eq!(vec, vec![9, 10, 11]);
 
+    let guard = pin();
     let mut iter_vec = Vec::new();
-    for &item in stack.iter() {
+    for &item in stack.iter(&guard) {
         iter_vec.push(item);
     }
-    
+    drop(guard);
+
     assert_eq!(iter_vec, vec![9, 10, 11]);
 
     stack.clear();
@@ -69,7 +69,7 @@ fn main() {
         handle.join().unwrap();
     }
 
-    println!("Final stack size: {}", stack.to_vec().len());
+    println!("Final stack size: {}", stack.len());
     println!("Stack contents: {:?}", stack.to_vec());
 
     let stack = Arc::new(LockFreeStack::new());
@@ -94,10 +94,7 @@ fn main() {
         handle.join().unwrap();
     }
 
-    println!(
-        "Final stack size after range operations: {}",
-        stack.to_vec().len()
-    );
+    println!("Final stack size after range operations: {}", stack.len());
     println!(
         "Stack contents after range operations: {:?}",
         stack.to_vec()
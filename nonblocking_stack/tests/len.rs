@@ -0,0 +1,66 @@
+use nonblocking_stack::{LenMode, LockFreeStack};
+use std::thread;
+
+const THREADS: u32 = 4;
+const OPS_PER_THREAD: u32 = 2_000;
+
+#[test]
+fn with_len_mode_carries_the_count_over() {
+    let stack = LockFreeStack::new();
+    stack.push(1);
+    stack.push(2);
+
+    let stack = stack.with_len_mode(LenMode::Exact);
+    assert_eq!(stack.len(), 2);
+    stack.push(3);
+    let stack = stack.with_len_mode(LenMode::Approximate);
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.try_pop(), Some(3));
+    assert_eq!(stack.len(), 2);
+}
+
+#[test]
+fn len_is_exact_once_pushes_and_pops_return() {
+    for mode in [LenMode::Approximate, LenMode::Exact] {
+        let stack = LockFreeStack::new().with_len_mode(mode);
+        thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let stack = &stack;
+                scope.spawn(move || {
+                    for value in 0..OPS_PER_THREAD {
+                        stack.push(value);
+                        if (value + thread) % 2 == 0 {
+                            stack.try_pop();
+                        }
+                    }
+                });
+            }
+        });
+
+        let remaining = THREADS * OPS_PER_THREAD / 2;
+        assert_eq!(stack.len(), remaining as usize);
+        assert_eq!(
+            stack.iter(&nonblocking_stack::pin()).count(),
+            remaining as usize
+        );
+    }
+}
+
+#[test]
+fn exact_len_never_runs_ahead_of_started_pushes() {
+    let stack = LockFreeStack::new().with_len_mode(LenMode::Exact);
+    let total = (THREADS * OPS_PER_THREAD) as usize;
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for value in 0..OPS_PER_THREAD {
+                    stack.push(value);
+                }
+            });
+        }
+        while stack.len() < total {
+            assert!(stack.len() <= total);
+        }
+    });
+    assert_eq!(stack.len(), total);
+}
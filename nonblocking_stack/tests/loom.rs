@@ -0,0 +1,136 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p nonblocking_stack --release --test loom`.
+use concurrency_core::PopError;
+use loom::sync::Arc;
+use loom::thread;
+use nonblocking_stack::{Blocking, BlockingConcurrentStack, LockFreeStack};
+
+#[test]
+fn concurrent_pushes_both_land() {
+    loom::model(|| {
+        let stack = Arc::new(LockFreeStack::new());
+        let producers: Vec<_> = (1..=2)
+            .map(|value| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || stack.push(value))
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut values = vec![stack.try_pop().unwrap(), stack.try_pop().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(stack.try_pop(), None);
+    });
+}
+
+#[test]
+fn pop_racing_push_sees_a_whole_item() {
+    loom::model(|| {
+        let stack = Arc::new(LockFreeStack::new());
+        stack.push(1);
+        let producer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push(2))
+        };
+
+        let first = stack.try_pop().unwrap();
+        producer.join().unwrap();
+        let second = stack.try_pop().unwrap();
+        assert!(matches!((first, second), (1, 2) | (2, 1)));
+        assert!(stack.is_empty());
+    });
+}
+
+#[test]
+fn racing_pops_take_each_item_once() {
+    loom::model(|| {
+        let stack = Arc::new(LockFreeStack::new());
+        stack.push(1);
+        stack.push(2);
+
+        let consumer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.try_pop())
+        };
+        let mut values = vec![stack.try_pop().unwrap(), consumer.join().unwrap().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(stack.try_pop(), None);
+    });
+}
+
+#[test]
+fn pop_range_racing_push_takes_a_consistent_run() {
+    loom::model(|| {
+        let stack = Arc::new(LockFreeStack::new());
+        stack.push(1);
+        let producer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push(2))
+        };
+
+        let mut values = stack.try_pop_range(2);
+        producer.join().unwrap();
+        values.extend(stack.try_pop_range(2));
+        assert!(values == [2, 1] || values == [1, 2]);
+    });
+}
+
+#[test]
+fn eliminated_push_hands_its_item_to_a_pop() {
+    loom::model(|| {
+        let stack = Arc::new(LockFreeStack::new().with_elimination(1));
+        stack.push(1);
+        let producer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.push(2))
+        };
+        let consumer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.try_pop())
+        };
+
+        let mut values = vec![stack.try_pop()];
+        producer.join().unwrap();
+        values.push(consumer.join().unwrap());
+        values.push(stack.try_pop());
+        values.push(stack.try_pop());
+        let mut values: Vec<_> = values.into_iter().flatten().collect();
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert!(stack.is_empty());
+        assert_eq!(stack.len(), 0);
+    });
+}
+
+#[test]
+fn blocking_pop_is_woken_by_push() {
+    loom::model(|| {
+        let stack = Arc::new(Blocking::new(LockFreeStack::new()));
+        let consumer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.pop())
+        };
+
+        stack.push(1).unwrap();
+        assert_eq!(consumer.join().unwrap(), Ok(1));
+    });
+}
+
+#[test]
+fn close_wakes_a_blocked_pop() {
+    loom::model(|| {
+        let stack = Arc::new(Blocking::new(LockFreeStack::<u32>::new()));
+        let consumer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || stack.pop())
+        };
+
+        stack.close();
+        assert_eq!(consumer.join().unwrap(), Err(PopError::Closed));
+    });
+}
@@ -0,0 +1,72 @@
+use nonblocking_stack::{pin, LenMode, LockFreeStack, Spin};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+// Runs random single-threaded operation sequences against the stack and a
+// `Vec` whose end is the top, checking every result. proptest shrinks a
+// failing sequence down to the shortest one that still fails.
+#[derive(Debug, Clone)]
+enum Op {
+    Push(u8),
+    TryPop,
+    PushRange(Vec<u8>),
+    TryPopRange(usize),
+    TryPeek,
+    ToVec,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u8>().prop_map(Op::Push),
+        2 => Just(Op::TryPop),
+        1 => vec(any::<u8>(), 0..8).prop_map(Op::PushRange),
+        1 => (0..8usize).prop_map(Op::TryPopRange),
+        1 => Just(Op::TryPeek),
+        1 => Just(Op::ToVec),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn lockfree_stack_matches_vec(ops in vec(op(), 0..64), exact in any::<bool>()) {
+        let mode = if exact { LenMode::Exact } else { LenMode::Approximate };
+        let stack = LockFreeStack::new().with_len_mode(mode);
+        let mut model = Vec::new();
+        for op in ops {
+            match op {
+                Op::Push(value) => {
+                    stack.push(value);
+                    model.push(value);
+                }
+                Op::TryPop => prop_assert_eq!(stack.try_pop(), model.pop()),
+                // The first item of a range ends up on top.
+                Op::PushRange(items) => {
+                    stack.push_range(items.clone());
+                    model.extend(items.into_iter().rev());
+                }
+                Op::TryPopRange(count) => {
+                    let split = model.len().saturating_sub(count);
+                    let expected: Vec<_> = model.drain(split..).rev().collect();
+                    prop_assert_eq!(stack.try_pop_range(count), expected);
+                }
+                Op::TryPeek => {
+                    prop_assert_eq!(stack.try_peek(&pin()).copied(), model.last().copied());
+                }
+                Op::ToVec => {
+                    let expected: Vec<_> = model.iter().rev().copied().collect();
+                    prop_assert_eq!(stack.to_vec(), expected);
+                }
+            }
+            prop_assert_eq!(stack.len(), model.len());
+            prop_assert_eq!(stack.is_empty(), model.is_empty());
+        }
+    }
+
+    #[test]
+    fn with_backoff_keeps_items_in_order(items in vec(any::<u8>(), 0..32)) {
+        let stack = LockFreeStack::new();
+        stack.push_range(items.clone());
+        let stack = stack.with_backoff(Spin::default());
+        prop_assert_eq!(stack.to_vec(), items);
+    }
+}
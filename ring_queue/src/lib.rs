@@ -0,0 +1,7 @@
+mod ring_queue;
+
+pub use concurrency_core::{
+    Backoff, Blocking, BlockingConcurrentQueue, ConcurrentQueue, Exponential, NoBackoff, Spin,
+    SpinThenYield,
+};
+pub use ring_queue::RingQueue;
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::thread;
+
+use ring_queue::{Blocking, BlockingConcurrentQueue, RingQueue};
+
+fn main() {
+    let queue = RingQueue::new(3);
+    assert_eq!(queue.capacity(), 3);
+    assert_eq!(queue.try_push(1), Ok(()));
+    assert_eq!(queue.try_push(2), Ok(()));
+    assert_eq!(queue.try_push(3), Ok(()));
+    assert!(queue.is_full());
+    assert_eq!(queue.try_push(4), Err(4));
+
+    assert_eq!(queue.try_pop(), Some(1));
+    assert_eq!(queue.try_push(4), Ok(()));
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.try_pop(), Some(2));
+    assert_eq!(queue.try_pop(), Some(3));
+    assert_eq!(queue.try_pop(), Some(4));
+    assert_eq!(queue.try_pop(), None);
+    assert!(queue.is_empty());
+
+    // Producers retry while the ring is full, consumers while it's empty.
+    let queue = Arc::new(RingQueue::new(64));
+    let producers: Vec<_> = (0..4)
+        .map(|i| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                for j in 0..1000 {
+                    let mut item = i * 1000 + j;
+                    while let Err(rejected) = queue.try_push(item) {
+                        item = rejected;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..4)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                let mut sum = 0;
+                for _ in 0..1000 {
+                    loop {
+                        if let Some(item) = queue.try_pop() {
+                            sum += item;
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                }
+                sum
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let sum: i32 = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+    assert_eq!(sum, (0..4000).sum());
+    assert!(queue.is_empty());
+
+    // `Blocking` turns the ring into a bounded blocking queue.
+    let queue = Arc::new(Blocking::new(RingQueue::new(2)));
+    let producer = {
+        let queue = Arc::clone(&queue);
+        thread::spawn(move || {
+            for i in 0..10 {
+                queue.push(i).unwrap();
+            }
+        })
+    };
+    let received: Vec<_> = (0..10).map(|_| queue.pop().unwrap()).collect();
+    producer.join().unwrap();
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+
+    println!("All tests passed!");
+}
@@ -0,0 +1,240 @@
+use concurrency_core::sync::atomic::{AtomicUsize, Ordering};
+use concurrency_core::sync::cell::UnsafeCell;
+use concurrency_core::{Backoff, ConcurrentQueue, SpinThenYield};
+use std::fmt;
+use std::mem::MaybeUninit;
+
+/// A fixed-capacity lock-free MPMC queue over a ring of slots, after
+/// Dmitry Vyukov's bounded queue.
+///
+/// All memory is allocated up front by [`RingQueue::new`]; pushes and pops
+/// never allocate. A full queue hands the value back from `try_push`.
+pub struct RingQueue<T, B: Backoff = SpinThenYield> {
+    head: Position,
+    tail: Position,
+    slots: Box<[Slot<T>]>,
+    backoff: B,
+}
+
+// Every slot carries a sequence number that says whose turn it is. A slot
+// at index `pos % capacity` is free for the push claiming position `pos`
+// when its sequence is `2 * pos`, and holds a value for the pop claiming
+// `pos` when it is `2 * pos + 1`. The pop then hands it to the push one lap
+// later by setting it to `2 * (pos + capacity)`. Counting in half-steps
+// keeps "full for this pop" and "free for the next push" apart even with a
+// single slot. Claiming a position is a CAS on `tail` or `head`; the
+// sequence store that follows publishes the value or the free slot, so a
+// slot is only ever touched by the one thread whose turn it is.
+struct Slot<T> {
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// Keeps producers and consumers from bouncing one cache line between them.
+#[repr(align(64))]
+struct Position(AtomicUsize);
+
+impl<T> RingQueue<T> {
+    /// Creates a queue that holds up to `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_parts(capacity, SpinThenYield::default())
+    }
+}
+
+impl<T, B: Backoff> RingQueue<T, B> {
+    fn with_parts(capacity: usize, backoff: B) -> Self {
+        assert!(capacity > 0, "RingQueue capacity must be at least 1");
+
+        Self {
+            head: Position(AtomicUsize::new(0)),
+            tail: Position(AtomicUsize::new(0)),
+            slots: (0..capacity)
+                .map(|index| Slot {
+                    sequence: AtomicUsize::new(index * 2),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect(),
+            backoff,
+        }
+    }
+
+    /// Switches to another [`Backoff`] strategy for retrying after a lost
+    /// race, e.g. `RingQueue::new(1024).with_backoff(Spin::new(4))`. Items
+    /// already in the queue move over in order.
+    pub fn with_backoff<B2: Backoff>(self, backoff: B2) -> RingQueue<T, B2> {
+        let queue = RingQueue::with_parts(self.capacity(), backoff);
+        while let Some(value) = self.try_pop() {
+            let _ = queue.try_push(value);
+        }
+
+        queue
+    }
+
+    /// Hands `value` back if the queue is full.
+    ///
+    /// A pop that has claimed the oldest item but not finished moving it
+    /// out still counts against the capacity, so this can fail on a queue
+    /// that is about to have room.
+    pub fn try_push(&self, value: T) -> Result<(), T> {
+        let mut step = 0;
+        let mut pos = self.tail.0.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[pos % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(pos.wrapping_mul(2)) as isize;
+            if lag < 0 {
+                return Err(value);
+            }
+            if lag > 0 {
+                // Another push took this position already.
+                self.backoff.wait(step);
+                step = step.saturating_add(1);
+                pos = self.tail.0.load(Ordering::Relaxed);
+                continue;
+            }
+
+            match self.tail.0.compare_exchange_weak(
+                pos,
+                pos.wrapping_add(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    slot.value
+                        .with_mut(|slot_value| unsafe { (*slot_value).write(value) });
+                    slot.sequence
+                        .store(pos.wrapping_mul(2).wrapping_add(1), Ordering::Release);
+
+                    return Ok(());
+                }
+                Err(current) => {
+                    pos = current;
+                    self.backoff.wait(step);
+                    step = step.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    /// Returns `None` if the queue is empty.
+    ///
+    /// A push that has claimed the next position but not finished writing
+    /// its value isn't visible yet, so this can miss an item that is about
+    /// to arrive.
+    pub fn try_pop(&self) -> Option<T> {
+        let mut step = 0;
+        let mut pos = self.head.0.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[pos % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(pos.wrapping_mul(2).wrapping_add(1)) as isize;
+            if lag < 0 {
+                return None;
+            }
+            if lag > 0 {
+                // Another pop took this position already.
+                self.backoff.wait(step);
+                step = step.saturating_add(1);
+                pos = self.head.0.load(Ordering::Relaxed);
+                continue;
+            }
+
+            match self.head.0.compare_exchange_weak(
+                pos,
+                pos.wrapping_add(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let value = slot
+                        .value
+                        .with(|slot_value| unsafe { (*slot_value).assume_init_read() });
+                    slot.sequence.store(
+                        pos.wrapping_add(self.slots.len()).wrapping_mul(2),
+                        Ordering::Release,
+                    );
+
+                    return Some(value);
+                }
+                Err(current) => {
+                    pos = current;
+                    self.backoff.wait(step);
+                    step = step.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Counts claimed positions, so pushes and pops that are still in
+    /// flight are already included. Never more than the capacity.
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.0.load(Ordering::SeqCst);
+            let head = self.head.0.load(Ordering::SeqCst);
+            // Only trust `head` if `tail` didn't move while it was read.
+            if self.tail.0.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head).min(self.capacity());
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+}
+
+impl<T, B: Backoff> ConcurrentQueue<T> for RingQueue<T, B> {
+    fn try_push(&self, item: T) -> Result<(), T> {
+        self.try_push(item)
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        self.try_pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, B: Backoff> fmt::Debug for RingQueue<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingQueue")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T, B: Backoff> Drop for RingQueue<T, B> {
+    fn drop(&mut self) {
+        let head = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        let mut pos = head;
+        while pos != tail {
+            self.slots[pos % self.slots.len()]
+                .value
+                .with_mut(|value| unsafe { (*value).assume_init_drop() });
+            pos = pos.wrapping_add(1);
+        }
+    }
+}
+
+unsafe impl<T: Send, B: Backoff + Send> Send for RingQueue<T, B> {}
+unsafe impl<T: Send, B: Backoff + Sync> Sync for RingQueue<T, B> {}
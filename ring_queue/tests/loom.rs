@@ -0,0 +1,94 @@
+#![cfg(loom)]
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test -p ring_queue --release --test loom`.
+use loom::sync::Arc;
+use loom::thread;
+use ring_queue::RingQueue;
+
+#[test]
+fn concurrent_pushes_both_land() {
+    loom::model(|| {
+        let queue = Arc::new(RingQueue::new(2));
+        let producers: Vec<_> = (1..=2)
+            .map(|value| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || queue.try_push(value).unwrap())
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut values = vec![queue.try_pop().unwrap(), queue.try_pop().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(queue.try_pop(), None);
+    });
+}
+
+#[test]
+fn pop_racing_push_keeps_fifo_order() {
+    loom::model(|| {
+        let queue = Arc::new(RingQueue::new(2));
+        queue.try_push(1).unwrap();
+        let producer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.try_push(2).unwrap())
+        };
+
+        assert_eq!(queue.try_pop(), Some(1));
+        producer.join().unwrap();
+        assert_eq!(queue.try_pop(), Some(2));
+    });
+}
+
+#[test]
+fn pop_racing_push_sees_a_whole_item() {
+    loom::model(|| {
+        let queue = Arc::new(RingQueue::new(1));
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.try_pop())
+        };
+
+        queue.try_push(String::from("item")).unwrap();
+        let popped = consumer.join().unwrap().or_else(|| queue.try_pop());
+        assert_eq!(popped.as_deref(), Some("item"));
+    });
+}
+
+#[test]
+fn racing_pops_take_each_item_once() {
+    loom::model(|| {
+        let queue = Arc::new(RingQueue::new(2));
+        queue.try_push(1).unwrap();
+        queue.try_push(2).unwrap();
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.try_pop())
+        };
+
+        let mut values = vec![queue.try_pop().unwrap(), consumer.join().unwrap().unwrap()];
+        values.sort();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(queue.try_pop(), None);
+    });
+}
+
+#[test]
+fn push_into_a_full_slot_waits_for_the_pop_to_finish() {
+    // With one slot, a push can only succeed once the pop has moved the old
+    // value out, so it never overwrites an item.
+    loom::model(|| {
+        let queue = Arc::new(RingQueue::new(1));
+        queue.try_push(1).unwrap();
+        let consumer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.try_pop())
+        };
+
+        let pushed = queue.try_push(2).is_ok();
+        assert_eq!(consumer.join().unwrap(), Some(1));
+        assert_eq!(queue.try_pop(), pushed.then_some(2));
+    });
+}
@@ -0,0 +1,56 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use ring_queue::{RingQueue, Spin};
+use std::collections::VecDeque;
+
+// Runs random single-threaded operation sequences against the ring and a
+// `VecDeque` capped at the same capacity, checking every result. proptest
+// shrinks a failing sequence down to the shortest one that still fails.
+#[derive(Debug, Clone)]
+enum Op {
+    TryPush(u8),
+    TryPop,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => any::<u8>().prop_map(Op::TryPush),
+        2 => Just(Op::TryPop),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn ring_queue_matches_bounded_vecdeque(ops in vec(op(), 0..64), capacity in 1..8usize) {
+        let queue = RingQueue::new(capacity);
+        let mut model = VecDeque::new();
+        for op in ops {
+            match op {
+                Op::TryPush(value) => {
+                    if model.len() < capacity {
+                        prop_assert_eq!(queue.try_push(value), Ok(()));
+                        model.push_back(value);
+                    } else {
+                        prop_assert_eq!(queue.try_push(value), Err(value));
+                    }
+                }
+                Op::TryPop => prop_assert_eq!(queue.try_pop(), model.pop_front()),
+            }
+            prop_assert_eq!(queue.len(), model.len());
+            prop_assert_eq!(queue.is_empty(), model.is_empty());
+            prop_assert_eq!(queue.is_full(), model.len() == capacity);
+        }
+    }
+
+    #[test]
+    fn with_backoff_keeps_items_in_order(items in vec(any::<u8>(), 0..32)) {
+        let queue = RingQueue::new(32);
+        for &item in &items {
+            prop_assert_eq!(queue.try_push(item), Ok(()));
+        }
+        let queue = queue.with_backoff(Spin::default());
+        prop_assert_eq!(queue.capacity(), 32);
+        let popped: Vec<_> = std::iter::from_fn(|| queue.try_pop()).collect();
+        prop_assert_eq!(popped, items);
+    }
+}